@@ -1,79 +1,127 @@
-use nom::{
-  bytes::complete::{is_not, take_until},
-  character::complete::char,
-  sequence::delimited
-};
-use quote::format_ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
 
-use crate::prelude::*;
-
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Endpoint {
-  pub name: String,
-  verb: String,
-  route: String,
-  params: Vec<String>,
-  extends: Vec<String>,
+  pub name: Ident,
+  /// The verbs the endpoint responds to, an endpoint with multiple verbs gets
+  /// a handler slot per verb on its `Router` instead of a single `endpoint`.
+  verbs: Vec<super::Verb>,
+  route: super::Route,
+  query: Option<super::Query>,
+  extends: Vec<Ident>,
   service_options: Vec<super::ServiceOption>
 }
 
-impl Endpoint {
-  pub fn parse(i: &str) -> IResult<&str, Self> {
-    let (i, _) = trim(i)?;
-    let (i, name) = take_until1(" ")(i)?;
-    let (i, extends) = many0(Self::parse_extend)(i)?;
-    let (i, options) = take_until1("=>")(i)?;
-    let (i, _) = trim(i)?;
-    let (i, _) = tag("=>")(i)?;
-    let (i, _) = trim(i)?;
-    let (i, verb) = take_until1(" ")(i)?;
-    let (i, _) = trim(i)?;
-    let (i, _) = tag("\"")(i)?;
-    let (i, route) = take_until1("\"")(i)?;
-    let (i, _) = tag("\"")(i)?;
-
-    let (_, options) = many0(super::ServiceOption::parse)(options)?;
-    let (_, params) = many0(Self::parse_param)(route)?;
-
-    Ok((
-      i,
-      Self {
-        name: name.trim().to_owned(),
-        verb: verb.trim().to_owned(),
-        route: route.to_owned(),
-        params,
-        extends,
-        service_options: options
+impl Parse for Endpoint {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name: Ident = input.parse().map_err(|e| {
+      syn::Error::new(
+        e.span(),
+        "expected an endpoint declaration like `name => VERB \"route\"`"
+      )
+    })?;
+
+    let mut extends = Vec::new();
+    let mut service_options = Vec::new();
+    while !input.peek(Token![=>]) {
+      if input.is_empty() || !input.peek(Ident) {
+        return Err(syn::Error::new(
+          input.span(),
+          "expected `=>` after the endpoint name"
+        ));
       }
-    ))
+
+      match input.fork().parse::<Ident>()? == "extend" {
+        true => extends.push(Self::parse_extend(input)?),
+        false => service_options.push(input.parse()?)
+      }
+    }
+
+    input.parse::<Token![=>]>()?;
+    let verbs = Self::parse_verbs(input)?;
+
+    let route: LitStr = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "route string must be quoted"))?;
+    let route = super::Route::parse(&route)?;
+    let query = match input.peek(Token![?]) {
+      true => Some(input.parse()?),
+      false => None
+    };
+
+    if input.peek(Token![,]) {
+      input.parse::<Token![,]>()?;
+    }
+
+    Ok(Self {
+      name,
+      verbs,
+      route,
+      query,
+      extends,
+      service_options
+    })
   }
+}
 
-  fn parse_extend(i: &str) -> IResult<&str, String> {
-    let (i, _) = trim(i)?;
-    let (i, _) = tag("extend")(i)?;
-    let (i, _) = take_until("(")(i)?;
-    let (i, ty) = delimited(char('('), is_not(")"), char(')'))(i)?;
+impl Endpoint {
+  /// Parses a list of verbs separated by `|`, like `GET | POST`
+  fn parse_verbs(input: ParseStream) -> syn::Result<Vec<super::Verb>> {
+    let mut verbs: Vec<super::Verb> = vec![input.parse()?];
 
-    Ok((i, ty.to_owned()))
+    while input.peek(Token![|]) {
+      let pipe = input.parse::<Token![|]>()?;
+      let verb: super::Verb = input.parse()?;
+
+      if verbs.iter().any(|v| v.name() == verb.name()) {
+        return Err(syn::Error::new(
+          pipe.span,
+          format!("the verb `{}` is listed more than once", verb.name())
+        ));
+      }
+
+      verbs.push(verb);
+    }
+
+    if verbs.len() > 1 {
+      for verb in &verbs {
+        verb.validate_handler_slot()?;
+      }
+    }
+
+    Ok(verbs)
   }
 
-  fn parse_param(route: &str) -> IResult<&str, String> {
-    let (i, _) = nom::bytes::complete::take_until("{")(route)?;
-    let (i, _) = tag("{")(i)?;
-    let (i, param) = take_until1("}")(i)?;
-    let (i, _) = tag("}")(i)?;
+  fn parse_extend(input: ParseStream) -> syn::Result<Ident> {
+    let keyword: Ident = input.parse()?;
 
-    Ok((i, param.to_owned()))
+    if !input.peek(syn::token::Paren) {
+      return Err(syn::Error::new(
+        keyword.span(),
+        "expected a function name between parentheses after `extend`"
+      ));
+    }
+
+    let content;
+    syn::parenthesized!(content in input);
+
+    content.parse()
   }
 
   pub fn emit(
-    &self, router_name: &str, router_type: super::RouterType
+    &self, router_name: &Ident, router_type: super::RouterType
   ) -> proc_macro2::TokenStream {
-    let name = format_ident!("{}", self.name);
-    let route = &self.route;
+    let name = &self.name;
+    let route = self.route.path();
 
     let url_fn = self.emit_url_fn(router_name);
-    let route_fn = self.emit_route_fn(router_name, router_type);
+    let hx_fns = self.emit_hx_fns();
+    let sse_fn = self.emit_sse_fn();
+    let ws_fn = self.emit_ws_fn();
+    let params = self.emit_params();
+    let query = self.emit_query();
+    let route_fn = self.emit_route_fns(router_name, router_type);
     let router = self.emit_router();
 
     let output = quote::quote!(
@@ -83,6 +131,11 @@ impl Endpoint {
         #router
 
         #url_fn
+        #hx_fns
+        #sse_fn
+        #ws_fn
+        #params
+        #query
         #route_fn
       }
     );
@@ -90,32 +143,207 @@ impl Endpoint {
     output
   }
 
-  fn emit_url_fn(&self, router_name: &str) -> proc_macro2::TokenStream {
-    let router_name = format_ident!("{}", router_name);
-    let route = &self.route;
-    let params = self.params.iter().map(|p| format_ident!("{}", p));
+  /// Every param is percent-encoded before being inserted in the route, the
+  /// catch-all ones like `{tail:.*}` keep their `/`.
+  fn emit_url_fn(&self, router_name: &Ident) -> proc_macro2::TokenStream {
+    let format = self.route.format();
+    let encoded = self.route.params.iter().map(|p| {
+      let name = &p.name;
+
+      match p.tail {
+        true => quote::quote!(lv_server::url::encode_tail(#name)),
+        false => quote::quote!(lv_server::url::encode_segment(#name))
+      }
+    });
+    let args = self.route.params.iter().map(super::route::Param::emit_arg);
 
     quote::quote!(
-      pub fn url(#(#params : impl std::fmt::Display),*) -> String {
+      pub fn url(#(#args),*) -> String {
         use lv_server::Fragment;
         use lv_server::View;
 
-        super::super::#router_name::url(&format!(#route))
+        super::super::#router_name::url(&format!(#format, #(#encoded),*))
+      }
+    )
+  }
+
+  /// Emits a `hx_{verb}()` function for each verb of the endpoint that HTMX
+  /// can send, returning the url along with its verb:
+  /// ```rs
+  /// button hx-delete=(api::delete_todo::hx_delete(index)) {"X"}
+  /// ```
+  fn emit_hx_fns(&self) -> proc_macro2::TokenStream {
+    let names: Vec<&Ident> = self.route.params.iter().map(|p| &p.name).collect();
+    let args: Vec<proc_macro2::TokenStream> = self
+      .route
+      .params
+      .iter()
+      .map(super::route::Param::emit_arg)
+      .collect();
+
+    self
+      .verbs
+      .iter()
+      .flat_map(|verb| verb.hx_verbs())
+      .map(|marker| {
+        let fn_name = quote::format_ident!("hx_{}", marker.to_lowercase());
+        let marker = quote::format_ident!("{}", marker);
+
+        quote::quote!(
+          pub fn #fn_name(#(#args),*) -> lv_server::htmx::HxUrl<lv_server::htmx::#marker> {
+            lv_server::htmx::HxUrl::new(url(#(#names),*))
+          }
+        )
+      })
+      .collect()
+  }
+
+  /// Emits a `sse_connect()` function for the `SSE` endpoints, returning the
+  /// url the clients connect to:
+  /// ```rs
+  /// div hx-ext="sse" sse-connect=(api::stream::sse_connect()) {}
+  /// ```
+  fn emit_sse_fn(&self) -> proc_macro2::TokenStream {
+    if !self
+      .verbs
+      .iter()
+      .any(|verb| matches!(verb, super::Verb::Sse(_)))
+    {
+      return quote::quote!();
+    }
+
+    let names = self.route.params.iter().map(|p| &p.name);
+    let args = self.route.params.iter().map(super::route::Param::emit_arg);
+
+    quote::quote!(
+      pub fn sse_connect(#(#args),*) -> lv_server::sse::SseConnect {
+        lv_server::sse::SseConnect::new(url(#(#names),*))
+      }
+    )
+  }
+
+  /// Emits a `ws_connect()` function for the `WS` endpoints, returning the url
+  /// the clients connect to:
+  /// ```rs
+  /// div hx-ext="ws" ws-connect=(api::socket::ws_connect()) {}
+  /// ```
+  fn emit_ws_fn(&self) -> proc_macro2::TokenStream {
+    if !self
+      .verbs
+      .iter()
+      .any(|verb| matches!(verb, super::Verb::Ws(_)))
+    {
+      return quote::quote!();
+    }
+
+    let names = self.route.params.iter().map(|p| &p.name);
+    let args = self.route.params.iter().map(super::route::Param::emit_arg);
+
+    quote::quote!(
+      pub fn ws_connect(#(#args),*) -> lv_server::ws::WsConnect {
+        lv_server::ws::WsConnect::new(url(#(#names),*))
       }
     )
   }
 
+  /// Emits a `Params` struct with one field per route param, so handlers can
+  /// extract the params with the same types the `url()` function expects:
+  /// ```rs
+  /// pub async fn endpoint(path: api::get_todo::Path) -> HttpResponse {
+  ///   let index: usize = path.index;
+  /// }
+  /// ```
+  fn emit_params(&self) -> proc_macro2::TokenStream {
+    if self.route.params.is_empty() {
+      return quote::quote!();
+    }
+
+    let fields = self.route.params.iter().map(|p| {
+      let name = &p.name;
+
+      match &p.ty {
+        Some(ty) => quote::quote!(pub #name: #ty),
+        None => quote::quote!(pub #name: String)
+      }
+    });
+
+    quote::quote!(
+      #[derive(lv_server::deps::serde::Deserialize)]
+      #[serde(crate = "lv_server::deps::serde")]
+      pub struct Params {
+        #(#fields),*
+      }
+
+      pub type Path = actix_web::web::Path<Params>;
+    )
+  }
+
+  /// Emits a `QueryParams` struct for the endpoints that declare query params,
+  /// along with its `Query` extractor and a `url_with_query()` function that
+  /// appends the encoded query params to the endpoint's url. It fails when a
+  /// param can't be encoded in a query string, like a `Vec` or a nested struct.
+  fn emit_query(&self) -> proc_macro2::TokenStream {
+    let Some(query) = &self.query else {
+      return quote::quote!();
+    };
+
+    let fields = query.params.iter().map(|p| {
+      let name = &p.name;
+      let ty = &p.ty;
+
+      quote::quote!(pub #name: #ty)
+    });
+    let names: Vec<&Ident> = self.route.params.iter().map(|p| &p.name).collect();
+    let args = self.route.params.iter().map(super::route::Param::emit_arg);
+
+    quote::quote!(
+      #[derive(lv_server::deps::serde::Deserialize, lv_server::deps::serde::Serialize)]
+      #[serde(crate = "lv_server::deps::serde")]
+      pub struct QueryParams {
+        #(#fields),*
+      }
+
+      pub type Query = actix_web::web::Query<QueryParams>;
+
+      pub fn url_with_query(
+        #(#args,)* __query: &QueryParams
+      ) -> Result<String, lv_server::url::QueryError> {
+        lv_server::url::with_query(url(#(#names),*), __query)
+      }
+    )
+  }
+
+  /// Emits a `route()` function for single verb endpoints, or one
+  /// `route_{verb}()` function per verb otherwise.
+  fn emit_route_fns(
+    &self, router_name: &Ident, router_type: super::RouterType
+  ) -> proc_macro2::TokenStream {
+    match self.verbs.as_slice() {
+      [verb] => self.emit_route_fn(
+        router_name,
+        router_type,
+        verb,
+        &quote::format_ident!("route")
+      ),
+      verbs => verbs
+        .iter()
+        .map(|verb| {
+          let fn_name = quote::format_ident!("route_{}", verb.handler_slot());
+
+          self.emit_route_fn(router_name, router_type, verb, &fn_name)
+        })
+        .collect()
+    }
+  }
+
   fn emit_route_fn(
-    &self, router_name: &str, router_type: super::RouterType
+    &self, router_name: &Ident, router_type: super::RouterType, verb: &super::Verb, fn_name: &Ident
   ) -> proc_macro2::TokenStream {
-    let router_name = format_ident!("{}", router_name);
-    let verb = format_ident!("{}", self.verb.to_lowercase());
-    let verb_upper = format_ident!("{}", self.verb.to_uppercase());
+    let verb_route = verb.emit_route(&quote::format_ident!("handler"));
     let extends: Vec<proc_macro2::TokenStream> = self
       .extends
       .iter()
       .map(|ex| {
-        let ex = format_ident!("{}", ex);
         quote::quote!(
           let route = super::super::#router_name::#ex(route);
         )
@@ -129,10 +357,7 @@ impl Endpoint {
         let route = super::super::#router_name::fragment_route(
           cfg,
           URL,
-          lv_server::csrf::csrf_protection(
-            actix_web::web::#verb(),
-            actix_web::http::Method::#verb_upper
-          ).to(handler)
+          #verb_route
           #(#service_options)*
         );
         #(#extends)*
@@ -141,10 +366,7 @@ impl Endpoint {
         let route = super::super::#router_name::view_route(
           cfg,
           URL,
-          lv_server::csrf::csrf_protection(
-            actix_web::web::#verb(),
-            actix_web::http::Method::#verb_upper
-          ).to(handler)
+          #verb_route
           #(#service_options)*
         );
         #(#extends)*
@@ -152,7 +374,7 @@ impl Endpoint {
     };
 
     quote::quote!(
-      pub fn route<F, ARGS>(cfg: &mut actix_web::web::ServiceConfig, handler: F)
+      pub fn #fn_name<F, ARGS>(cfg: &mut actix_web::web::ServiceConfig, handler: F)
       where
         F: actix_web::Handler<ARGS>,
         ARGS: actix_web::FromRequest + 'static,
@@ -165,14 +387,91 @@ impl Endpoint {
     )
   }
 
+  /// The `Router` of single verb endpoints expects an `endpoint` handler while
+  /// multiple verb endpoints expect one handler per verb, named after the verb:
+  /// ```rs
+  /// // edit => GET | POST "/edit"
+  /// impl api::edit::Router {
+  ///   pub async fn get() -> HttpResponse {}
+  ///   pub async fn post() -> HttpResponse {}
+  /// }
+  /// ```
   fn emit_router(&self) -> proc_macro2::TokenStream {
+    let routes: Vec<proc_macro2::TokenStream> = match self.verbs.as_slice() {
+      [_] => vec![quote::quote!(route(cfg, Router::endpoint);)],
+      verbs => verbs
+        .iter()
+        .map(|verb| {
+          let slot = verb.handler_slot();
+          let fn_name = quote::format_ident!("route_{}", slot);
+
+          quote::quote!(#fn_name(cfg, Router::#slot);)
+        })
+        .collect()
+    };
+
     quote::quote!(
       pub struct Router;
       impl lv_server::WithRouter for Router {
         fn router(cfg: &mut actix_web::web::ServiceConfig) {
-          route(cfg, Router::endpoint);
+          #(#routes)*
         }
       }
     )
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(endpoint: &str) -> syn::Result<Endpoint> {
+    syn::parse_str(endpoint)
+  }
+
+  fn slots(endpoint: &Endpoint) -> Vec<String> {
+    endpoint
+      .verbs
+      .iter()
+      .map(|verb| verb.handler_slot().to_string())
+      .collect()
+  }
+
+  #[test]
+  fn single_verb() {
+    let endpoint = parse("get_index => GET \"/\"").unwrap();
+
+    assert_eq!(slots(&endpoint), ["get"]);
+  }
+
+  #[test]
+  fn multiple_verbs_get_a_handler_slot_each() {
+    let endpoint = parse("edit_todo => GET | POST | METHOD(\"PURGE\") \"/{index}\"").unwrap();
+
+    assert_eq!(slots(&endpoint), ["get", "post", "purge"]);
+  }
+
+  #[test]
+  fn duplicate_verbs_are_rejected() {
+    assert!(parse("edit_todo => GET | POST | GET \"/\"").is_err());
+    assert!(parse("edit_todo => METHOD(\"PURGE\") | METHOD(\"PURGE\") \"/\"").is_err());
+  }
+
+  #[test]
+  fn verbs_without_a_handler_slot_are_rejected() {
+    for verbs in [
+      "GET | ANY",
+      "ANY | POST",
+      "SSE | GET",
+      "GET | WS",
+      "GET | METHOD(\"X-PURGE\")"
+    ] {
+      assert!(
+        parse(&format!("edit_todo => {verbs} \"/\"")).is_err(),
+        "`{verbs}` should be rejected"
+      );
+    }
+
+    assert!(parse("any_index => ANY \"/\"").is_ok());
+  }
+}
@@ -7,3 +7,12 @@ pub use endpoint::Endpoint;
 
 mod service_option;
 pub use service_option::ServiceOption;
+
+mod route;
+pub use route::Route;
+
+mod query;
+pub use query::Query;
+
+mod verb;
+pub use verb::Verb;
@@ -0,0 +1,56 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Token};
+
+/// The typed query params of an endpoint, declared right after its route:
+/// ```rs
+/// get_index => GET "/" ?(page: u32, q: Option<String>)
+/// ```
+#[derive(Debug, Clone)]
+pub struct Query {
+  pub params: Punctuated<QueryParam, Token![,]>
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryParam {
+  pub name: Ident,
+  pub ty: syn::Type
+}
+
+impl Parse for Query {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let question_mark = input.parse::<Token![?]>()?;
+
+    if !input.peek(syn::token::Paren) {
+      return Err(syn::Error::new(
+        question_mark.span,
+        "expected the query params between parentheses after `?`, like `?(page: u32)`"
+      ));
+    }
+
+    let content;
+    syn::parenthesized!(content in input);
+
+    Ok(Self {
+      params: content.parse_terminated(QueryParam::parse, Token![,])?
+    })
+  }
+}
+
+impl Parse for QueryParam {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name: Ident = input.parse()?;
+
+    if !input.peek(Token![:]) {
+      return Err(syn::Error::new(
+        name.span(),
+        format!("query param `{name}` must be typed, like `{name}: String`")
+      ));
+    }
+
+    input.parse::<Token![:]>()?;
+    let ty = input.parse()?;
+
+    Ok(Self { name, ty })
+  }
+}
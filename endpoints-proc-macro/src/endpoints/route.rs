@@ -0,0 +1,247 @@
+use proc_macro2::Span;
+use syn::{Ident, LitStr};
+
+/// The route of an endpoint, as written in the macro:
+/// ```rs
+/// "/todos/{index: usize}/{slug}"
+/// ```
+#[derive(Debug, Clone)]
+pub struct Route {
+  span: Span,
+
+  /// The route as actix expects it, with the types of the params removed
+  path: String,
+
+  /// The route with its params replaced by `{}`, used by the generated `url()`
+  /// functions to format the params into the route.
+  format: String,
+
+  pub params: Vec<Param>
+}
+
+/// A `{param}` segment of a route, which can optionally be typed using the
+/// `{param: Type}` syntax.
+///
+/// Actix patterns like `{param:\d+}` are forwarded to actix as is, what follows
+/// the `:` is only read as a type when it parses as one. The catch-all params
+/// are declared the actix way with `{param:.*}`.
+#[derive(Debug, Clone)]
+pub struct Param {
+  pub name: Ident,
+  pub ty: Option<syn::Type>,
+
+  /// Whether the param is a catch-all like `{tail:.*}` whose value can span
+  /// multiple segments, in which case its `/` aren't percent-encoded.
+  pub tail: bool
+}
+
+impl Route {
+  pub fn parse(lit: &LitStr) -> syn::Result<Self> {
+    let span = lit.span();
+    let value = lit.value();
+    let mut rest = value.as_str();
+
+    let mut path = String::new();
+    let mut format = String::new();
+    let mut params = Vec::new();
+
+    while let Some(start) = rest.find('{') {
+      let Some(end) = closing_brace(&rest[start..]).map(|end| start + end) else {
+        return Err(syn::Error::new(
+          span,
+          "route parameter is missing its closing `}`"
+        ));
+      };
+
+      path.push_str(&rest[..start]);
+      format.push_str(&escape_format(&rest[..start]));
+      format.push_str("{}");
+
+      let (param, actix) = Param::parse(&rest[start + 1..end], span)?;
+      path.push_str(&actix);
+      params.push(param);
+
+      rest = &rest[end + 1..];
+    }
+
+    path.push_str(rest);
+    format.push_str(&escape_format(rest));
+
+    Ok(Self {
+      span,
+      path,
+      format,
+      params
+    })
+  }
+
+  /// The route as a string literal that can be given to actix
+  pub fn path(&self) -> LitStr {
+    LitStr::new(&self.path, self.span)
+  }
+
+  /// The format string literal the params can be formatted into
+  pub fn format(&self) -> LitStr {
+    LitStr::new(&self.format, self.span)
+  }
+}
+
+impl Param {
+  /// The patterns of the catch-all params, that match the rest of the path
+  const TAILS: [&'static str; 2] = [".*", ".+"];
+
+  /// Parses the content of a `{param}` segment, returns the param along with
+  /// the segment as actix expects it.
+  fn parse(segment: &str, span: Span) -> syn::Result<(Self, String)> {
+    let (name, pattern) = match segment.split_once(':') {
+      Some((name, pattern)) => (name, Some(pattern.trim())),
+      None => (segment, None)
+    };
+
+    let Ok(name) = syn::parse_str::<Ident>(name.trim()) else {
+      return Err(syn::Error::new(
+        span,
+        format!("route parameter `{{{segment}}}` must start with a valid identifier")
+      ));
+    };
+    let name = Ident::new(&name.to_string(), span);
+
+    // what follows the `:` is the type of the param when it parses as one, and
+    // an actix pattern otherwise
+    let (ty, pattern) = match pattern.map(|pattern| (pattern, syn::parse_str::<syn::Type>(pattern)))
+    {
+      Some(("", _)) => {
+        return Err(syn::Error::new(
+          span,
+          format!("expected a type or a pattern after `:` in route parameter `{{{segment}}}`")
+        ))
+      }
+      Some((_, Ok(ty))) => (Some(ty), None),
+      Some((pattern, Err(_))) => (None, Some(pattern)),
+      None => (None, None)
+    };
+
+    let tail = Self::TAILS.contains(&pattern.unwrap_or_default());
+    let actix = match pattern {
+      Some(pattern) => format!("{{{name}:{pattern}}}"),
+      None => format!("{{{name}}}")
+    };
+
+    Ok((Self { name, ty, tail }, actix))
+  }
+
+  /// Emits the param as an argument of the generated `url()` functions. Typed
+  /// params are taken as is while the others accept anything that implements
+  /// [Display](std::fmt::Display).
+  pub fn emit_arg(&self) -> proc_macro2::TokenStream {
+    let name = &self.name;
+
+    match &self.ty {
+      Some(ty) => quote::quote!(#name: #ty),
+      None => quote::quote!(#name: impl std::fmt::Display)
+    }
+  }
+}
+
+/// The index of the `}` that closes the `{` the segment starts with, the
+/// braces of the actix patterns like `\d{3}` are skipped.
+fn closing_brace(segment: &str) -> Option<usize> {
+  let mut depth = 0;
+
+  for (index, c) in segment.char_indices() {
+    match c {
+      '{' => depth += 1,
+      '}' if depth == 1 => return Some(index),
+      '}' => depth -= 1,
+      _ => {}
+    }
+  }
+
+  None
+}
+
+/// Escapes the static parts of the route for the `format!` of the `url()`
+/// functions.
+fn escape_format(value: &str) -> String {
+  value.replace('{', "{{").replace('}', "}}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(route: &str) -> syn::Result<Route> {
+    Route::parse(&LitStr::new(route, Span::call_site()))
+  }
+
+  #[test]
+  fn untyped_params() {
+    let route = parse("/todos/{index}/{slug}").unwrap();
+
+    assert_eq!(route.path, "/todos/{index}/{slug}");
+    assert_eq!(route.format, "/todos/{}/{}");
+    assert_eq!(route.params.len(), 2);
+    assert!(route.params.iter().all(|p| p.ty.is_none()));
+  }
+
+  #[test]
+  fn typed_params() {
+    let route = parse("/todos/{index: usize}/{id:Uuid}").unwrap();
+
+    assert_eq!(route.path, "/todos/{index}/{id}");
+    assert_eq!(route.params[0].name, "index");
+    assert!(route.params[0].ty.is_some());
+    assert!(route.params[1].ty.is_some());
+  }
+
+  #[test]
+  fn actix_patterns() {
+    let route = parse("/todos/{id:\\d{3}}/{name: [a-z]+}/{slug:.+}").unwrap();
+
+    assert_eq!(route.path, "/todos/{id:\\d{3}}/{name:[a-z]+}/{slug:.+}");
+    assert_eq!(route.format, "/todos/{}/{}/{}");
+    assert!(route.params.iter().all(|p| p.ty.is_none()));
+  }
+
+  #[test]
+  fn types_take_precedence_over_patterns() {
+    let route = parse("/todos/{id: Vec<u8>}/{name:foo}").unwrap();
+
+    assert_eq!(route.path, "/todos/{id}/{name}");
+    assert!(route.params.iter().all(|p| p.ty.is_some()));
+    assert!(parse("/todos/{id:}").is_err());
+  }
+
+  #[test]
+  fn invalid_params() {
+    assert!(parse("/todos/{index").is_err());
+    assert!(parse("/todos/{}").is_err());
+    assert!(parse("/todos/{1index}").is_err());
+  }
+
+  #[test]
+  fn tails() {
+    let route = parse("/files/{dir}/{path:.*}").unwrap();
+
+    assert_eq!(route.path, "/files/{dir}/{path:.*}");
+    assert_eq!(route.format, "/files/{}/{}");
+    assert!(!route.params[0].tail);
+    assert!(route.params[1].tail);
+    assert!(parse("/files/{path:.+}").unwrap().params[0].tail);
+  }
+
+  #[test]
+  fn other_patterns_are_not_tails() {
+    let route = parse("/files/{path:[a-z/]+}/{name:\\w*}").unwrap();
+
+    assert!(route.params.iter().all(|p| !p.tail));
+  }
+
+  #[test]
+  fn static_braces_are_escaped() {
+    let route = parse("/a}b/{index}").unwrap();
+
+    assert_eq!(route.path, "/a}b/{index}");
+    assert_eq!(route.format, "/a}}b/{}");
+  }
+}
@@ -1,23 +1,18 @@
-use std::fmt::Display;
-
-use crate::prelude::*;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Token};
 
 use super::Endpoint;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum RouterType {
+  #[default]
   Fragment,
   View
 }
-impl Default for RouterType {
-  fn default() -> Self {
-    Self::Fragment
-  }
-}
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Router {
-  name: String,
+  name: Ident,
   endpoints: Vec<Endpoint>,
 
   /// controls what kind of endpoint it emits. By default it emits endpoints
@@ -31,40 +26,58 @@ pub struct Router {
   impl_router: bool
 }
 
-impl Router {
-  pub fn parse(i: &str) -> IResult<&str, Self> {
-    let (i, _) = trim(i)?;
-    let (i, name_and_type) = take_until1("{")(i)?;
-    let (_, (name, router_type)) = Self::parse_name_and_type(name_and_type)?;
-    let (i, _) = tag("{")(i)?;
-    let (i, endpoints) = many0(Endpoint::parse)(i)?;
-    let (i, _) = nom::bytes::complete::take_until("}")(i)?;
-
-    Ok((
-      i,
-      Self {
-        endpoints,
-        name: name.trim().to_owned(),
-        router_type,
-        impl_router: true
-      }
-    ))
-  }
+impl Parse for Router {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name: Ident = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "expected the name of the router"))?;
+    let router_type = match input.peek(Token![as]) {
+      true => Self::parse_router_type(input)?,
+      false => RouterType::Fragment
+    };
 
-  fn parse_name_and_type(i: &str) -> IResult<&str, (&str, RouterType)> {
-    let (i, _) = trim(i)?;
-    let (i, name) = take_while1(|c| c != ' ')(i)?;
-    let (i, some_router_type) = nom::combinator::opt(Self::parse_router_type)(i.trim())?;
+    if !input.peek(syn::token::Brace) {
+      return Err(syn::Error::new(
+        input.span(),
+        "expected `{` after the name of the router"
+      ));
+    }
 
-    Ok((i, (name, some_router_type.unwrap_or(RouterType::Fragment))))
-  }
+    let content;
+    syn::braced!(content in input);
+
+    let mut endpoints = Vec::new();
+    while !content.is_empty() {
+      endpoints.push(content.parse()?);
+    }
+
+    if !input.is_empty() {
+      return Err(input.error("unexpected tokens after the endpoints"));
+    }
 
-  fn parse_router_type(i: &str) -> IResult<&str, RouterType> {
-    let (i, _) = tag("as")(i)?;
+    Ok(Self {
+      name,
+      endpoints,
+      router_type,
+      impl_router: true
+    })
+  }
+}
 
-    match i.trim() {
-      "View" | "view" => Ok((i, RouterType::View)),
-      _ => Ok((i, RouterType::Fragment))
+impl Router {
+  fn parse_router_type(input: ParseStream) -> syn::Result<RouterType> {
+    input.parse::<Token![as]>()?;
+    let router_type: Ident = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "expected `View` or `Fragment` after `as`"))?;
+
+    match router_type.to_string().as_str() {
+      "View" | "view" => Ok(RouterType::View),
+      "Fragment" | "fragment" => Ok(RouterType::Fragment),
+      _ => Err(syn::Error::new(
+        router_type.span(),
+        format!("unknown router type `{router_type}`, expected `View` or `Fragment`")
+      ))
     }
   }
 
@@ -74,7 +87,7 @@ impl Router {
       .endpoints
       .iter()
       .map(|e| {
-        let module = quote::format_ident!("{}", e.name);
+        let module = &e.name;
 
         quote::quote!(
           cfg.configure(#module::Router::router);
@@ -95,7 +108,7 @@ impl Router {
   fn emit_router_impl(&self) -> proc_macro2::TokenStream {
     match self.impl_router {
       true => {
-        let name = quote::format_ident!("{}", self.name);
+        let name = &self.name;
 
         quote::quote!(
           impl lv_server::WithRouter for super::#name
@@ -111,10 +124,8 @@ impl Router {
       }
     }
   }
-}
 
-impl Display for Router {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+  pub fn emit(&self) -> proc_macro2::TokenStream {
     use proc_macro2::TokenStream;
     let endpoint_mods: Vec<TokenStream> = self
       .endpoints
@@ -125,7 +136,7 @@ impl Display for Router {
     let router = self.emit_router();
     let router_impl = self.emit_router_impl();
 
-    let output = quote::quote! {
+    quote::quote! {
       pub mod api {
         use super::*;
 
@@ -134,8 +145,6 @@ impl Display for Router {
 
         #(#endpoint_mods)*
       }
-    };
-
-    write!(f, "{output}")
+    }
   }
 }
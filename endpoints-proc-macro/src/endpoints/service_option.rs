@@ -1,52 +1,52 @@
-use nom::{
-  branch::alt,
-  bytes::complete::{is_not, take_until},
-  character::complete::char,
-  sequence::delimited
-};
-use quote::format_ident;
-
-use crate::prelude::*;
+use syn::parse::{Parse, ParseStream};
+use syn::Ident;
 
 #[derive(Debug, Clone)]
 pub enum ServiceOption {
-  Wrap(String),
-  WrapFromFn(String)
+  Wrap(syn::Expr),
+  WrapFromFn(syn::Path)
 }
 
-impl ServiceOption {
-  pub fn parse(i: &str) -> IResult<&str, Self> {
-    let (i, _) = trim(i)?;
-
-    alt((Self::parse_wrap_from_fn, Self::parse_wrap))(i)
-  }
+impl Parse for ServiceOption {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let keyword: Ident = input.parse()?;
+    let option = keyword.to_string();
 
-  fn parse_wrap(i: &str) -> IResult<&str, Self> {
-    let (i, _) = tag("wrap")(i)?;
-    let (i, _) = take_until("(")(i)?;
-    let (i, ty) = delimited(char('('), is_not(")"), char(')'))(i)?;
+    if option != "wrap" && option != "wrap_from_fn" {
+      return Err(syn::Error::new(
+        keyword.span(),
+        format!(
+          "unknown endpoint option `{option}`, expected `extend(..)`, `wrap(..)` or `wrap_from_fn(..)`"
+        )
+      ));
+    }
 
-    Ok((i, Self::Wrap(ty.to_owned())))
-  }
+    if !input.peek(syn::token::Paren) {
+      return Err(syn::Error::new(
+        keyword.span(),
+        format!("expected parentheses after `{option}`")
+      ));
+    }
 
-  fn parse_wrap_from_fn(i: &str) -> IResult<&str, Self> {
-    let (i, _) = tag("wrap_from_fn")(i)?;
-    let (i, _) = take_until("(")(i)?;
-    let (i, ty) = delimited(char('('), is_not(")"), char(')'))(i)?;
+    let content;
+    syn::parenthesized!(content in input);
 
-    Ok((i, Self::WrapFromFn(ty.to_owned())))
+    match option.as_str() {
+      "wrap" => Ok(Self::Wrap(content.parse()?)),
+      _ => Ok(Self::WrapFromFn(content.parse()?))
+    }
   }
+}
 
+impl ServiceOption {
   pub fn emit(&self) -> proc_macro2::TokenStream {
     match self {
-      ServiceOption::Wrap(s) => {
-        let ty = format_ident!("{}", s);
+      ServiceOption::Wrap(ty) => {
         quote::quote!(
           .wrap(#ty)
         )
       }
-      ServiceOption::WrapFromFn(s) => {
-        let ty = format_ident!("{}", s);
+      ServiceOption::WrapFromFn(ty) => {
         quote::quote!(
           .wrap(from_fn(#ty))
         )
@@ -0,0 +1,245 @@
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr};
+
+/// The HTTP method an endpoint responds to:
+/// ```rs
+/// get_index => GET "/"
+/// any_index => ANY "/"
+/// purge_cache => METHOD("PURGE") "/cache"
+/// stream => SSE "/stream"
+/// socket => WS "/socket"
+/// ```
+#[derive(Debug, Clone)]
+pub enum Verb {
+  /// One of the [Verb::STANDARD] methods
+  Standard(Ident),
+
+  /// Matches any method, the CSRF checks are then performed on each request
+  /// depending on its actual method.
+  Any(Ident),
+
+  /// A custom method, declared with `METHOD("NAME")`
+  Custom(LitStr),
+
+  /// A Server-Sent Events stream, a GET endpoint that the clients connect to
+  /// with the `sse-connect` attribute.
+  Sse(Ident),
+
+  /// A WebSocket, a GET endpoint that is upgraded once the clients connect to
+  /// it with the `ws-connect` attribute.
+  Ws(Ident)
+}
+
+impl Verb {
+  pub const STANDARD: [&'static str; 7] =
+    ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+  /// The name of the verb as it's written in the macro, or the name of the
+  /// method for custom ones.
+  pub fn name(&self) -> String {
+    match self {
+      Verb::Standard(verb) => verb.to_string(),
+      Verb::Any(_) => "ANY".to_owned(),
+      Verb::Custom(name) => name.value(),
+      Verb::Sse(_) => "SSE".to_owned(),
+      Verb::Ws(_) => "WS".to_owned()
+    }
+  }
+
+  /// The name of the handler the `Router` of a multiple verbs endpoint expects
+  /// for this verb, `GET` expects a `get` handler for example.
+  pub fn handler_slot(&self) -> Ident {
+    let span = match self {
+      Verb::Standard(verb) | Verb::Any(verb) | Verb::Sse(verb) | Verb::Ws(verb) => verb.span(),
+      Verb::Custom(name) => name.span()
+    };
+
+    Ident::new(&self.name().to_lowercase(), span)
+  }
+
+  /// Ensures the verb can be used in a multiple verbs endpoint.
+  pub fn validate_handler_slot(&self) -> syn::Result<()> {
+    match self {
+      Verb::Any(verb) => Err(syn::Error::new(
+        verb.span(),
+        "`ANY` already accepts every method and can't be combined with other verbs"
+      )),
+      Verb::Sse(verb) => Err(syn::Error::new(
+        verb.span(),
+        "`SSE` endpoints are streams and can't be combined with other verbs"
+      )),
+      Verb::Ws(verb) => Err(syn::Error::new(
+        verb.span(),
+        "`WS` endpoints are upgraded to WebSockets and can't be combined with other verbs"
+      )),
+      Verb::Custom(name) if syn::parse_str::<Ident>(&name.value().to_lowercase()).is_err() => {
+        Err(syn::Error::new(
+          name.span(),
+          format!(
+            "`{}` can't be used as a handler name, custom methods combined with other verbs must be valid identifiers",
+            name.value().to_lowercase()
+          )
+        ))
+      }
+      _ => Ok(())
+    }
+  }
+
+  /// The `lv_server::htmx::HxVerb` markers of the HTMX attributes that
+  /// can send a request to this verb, `ANY` accepts all of them while `HEAD`,
+  /// `OPTIONS`, `SSE`, `WS` and the custom methods have no HTMX attribute.
+  pub fn hx_verbs(&self) -> Vec<&'static str> {
+    const HX_VERBS: [(&str, &str); 5] = [
+      ("GET", "Get"),
+      ("POST", "Post"),
+      ("PUT", "Put"),
+      ("PATCH", "Patch"),
+      ("DELETE", "Delete")
+    ];
+
+    match self {
+      Verb::Any(_) => HX_VERBS.iter().map(|(_, marker)| *marker).collect(),
+      Verb::Standard(verb) => HX_VERBS
+        .iter()
+        .filter(|(name, _)| verb == name)
+        .map(|(_, marker)| *marker)
+        .collect(),
+      Verb::Custom(_) | Verb::Sse(_) | Verb::Ws(_) => Vec::new()
+    }
+  }
+
+  /// Emits the expression that creates the actix route for this verb and its
+  /// handler, with the CSRF protection applied to it.
+  pub fn emit_route(&self, handler: &Ident) -> proc_macro2::TokenStream {
+    match self {
+      Verb::Any(_) => quote::quote!(
+        lv_server::csrf::csrf_protection_any(actix_web::web::route().to(#handler))
+      ),
+      _ => {
+        let method = self.emit_method();
+
+        quote::quote!(
+          lv_server::csrf::csrf_protection(actix_web::web::method(#method).to(#handler), #method)
+        )
+      }
+    }
+  }
+
+  fn emit_method(&self) -> proc_macro2::TokenStream {
+    match self {
+      Verb::Standard(verb) => quote::quote!(actix_web::http::Method::#verb),
+      Verb::Sse(_) | Verb::Ws(_) => quote::quote!(actix_web::http::Method::GET),
+      Verb::Custom(name) => {
+        let bytes = syn::LitByteStr::new(name.value().as_bytes(), name.span());
+
+        quote::quote!(
+          actix_web::http::Method::from_bytes(#bytes)
+            .expect("custom methods are validated by the endpoints! macro")
+        )
+      }
+      Verb::Any(_) => unreachable!("ANY endpoints don't have a specific method")
+    }
+  }
+
+  fn parse_custom(input: ParseStream, keyword: &Ident) -> syn::Result<LitStr> {
+    if !input.peek(syn::token::Paren) {
+      return Err(syn::Error::new(
+        keyword.span(),
+        "expected the name of the method between parentheses, like `METHOD(\"PURGE\")`"
+      ));
+    }
+
+    let content;
+    syn::parenthesized!(content in input);
+    let name: LitStr = content.parse()?;
+    let value = name.value();
+
+    let is_token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if value.is_empty() || !value.chars().all(is_token) {
+      return Err(syn::Error::new(
+        name.span(),
+        format!("`{value}` is not a valid HTTP method name")
+      ));
+    }
+
+    Ok(name)
+  }
+}
+
+impl Parse for Verb {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let verb: Ident = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "expected HTTP verb after `=>`"))?;
+    let name = verb.to_string();
+
+    match name.as_str() {
+      "ANY" => Ok(Self::Any(verb)),
+      "METHOD" => Ok(Self::Custom(Self::parse_custom(input, &verb)?)),
+      "SSE" => Ok(Self::Sse(verb)),
+      "WS" => Ok(Self::Ws(verb)),
+      _ if Self::STANDARD.contains(&name.as_str()) => Ok(Self::Standard(verb)),
+      _ => Err(syn::Error::new(
+        verb.span(),
+        format!(
+          "unknown HTTP verb `{verb}`, expected one of {}, ANY, SSE, WS or METHOD(\"NAME\")",
+          Self::STANDARD.join(", ")
+        )
+      ))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(verb: &str) -> syn::Result<Verb> {
+    syn::parse_str(verb)
+  }
+
+  #[test]
+  fn standard_verbs() {
+    for name in Verb::STANDARD {
+      let verb = parse(name).unwrap();
+
+      assert!(matches!(verb, Verb::Standard(_)));
+      assert_eq!(verb.handler_slot(), name.to_lowercase());
+    }
+  }
+
+  #[test]
+  fn any_accepts_every_hx_verb() {
+    let verb = parse("ANY").unwrap();
+
+    assert!(matches!(verb, Verb::Any(_)));
+    assert_eq!(verb.hx_verbs(), ["Get", "Post", "Put", "Patch", "Delete"]);
+    assert!(verb.validate_handler_slot().is_err());
+  }
+
+  #[test]
+  fn custom_methods() {
+    let verb = parse("METHOD(\"PURGE\")").unwrap();
+
+    assert!(matches!(verb, Verb::Custom(_)));
+    assert_eq!(verb.name(), "PURGE");
+    assert_eq!(verb.handler_slot(), "purge");
+    assert!(verb.hx_verbs().is_empty());
+    assert!(verb.validate_handler_slot().is_ok());
+  }
+
+  #[test]
+  fn invalid_verbs() {
+    for verb in [
+      "get",
+      "FETCH",
+      "METHOD",
+      "METHOD()",
+      "METHOD(PURGE)",
+      "METHOD(\"\")",
+      "METHOD(\"PUR GE\")"
+    ] {
+      assert!(parse(verb).is_err(), "`{verb}` should be rejected");
+    }
+  }
+}
@@ -1,26 +1,364 @@
-use crate::prelude::*;
+use syn::parse::{Parse, ParseStream};
+use syn::{FieldsNamed, Ident, LitStr, Token};
 
-#[derive(Debug, Clone, Default)]
+/// An event declared in the `events!` macro, optionally with a payload that is
+/// sent as the detail of the event and a filter that comes before the
+/// modifiers:
+/// ```rs
+/// Reload "from:body"
+/// Updated { id: u64 } "from:body"
+/// Saved "[ctrlKey] from:body"
+/// ```
+#[derive(Debug, Clone)]
 pub struct Event {
-  pub name: String,
-  pub modifier: String
+  pub name: Ident,
+  pub fields: Option<FieldsNamed>,
+  pub filter: Option<String>,
+  pub modifier: LitStr
 }
 
 impl Event {
-  pub fn parse(i: &str) -> IResult<&str, Self> {
-    let (i, _) = trim(i)?;
-    let (i, name) = take_until1(" ")(i)?;
-    let (i, _) = trim(i)?;
-    let (i, _) = tag("\"")(i)?;
-    let (i, modifier) = take_until1("\"")(i)?;
-    let (i, _) = tag("\"")(i)?;
-
-    Ok((
-      i,
-      Self {
-        name: name.trim().to_owned(),
-        modifier: modifier.trim().to_owned()
+  /// The identifiers of the payload's fields, if any
+  pub fn field_names(&self) -> Vec<&Ident> {
+    self
+      .fields
+      .iter()
+      .flat_map(|fields| fields.named.iter())
+      .filter_map(|field| field.ident.as_ref())
+      .collect()
+  }
+
+  /// The name of the listener constant generated for the event, `Updated`
+  /// becomes `UPDATED` and `ReloadAll` becomes `RELOAD_ALL`.
+  pub fn listener_name(&self) -> Ident {
+    let mut name = String::new();
+
+    for (i, c) in self.name.to_string().chars().enumerate() {
+      if c.is_uppercase() && i > 0 && !name.ends_with('_') {
+        name.push('_');
       }
-    ))
+
+      name.push(c.to_ascii_uppercase());
+    }
+
+    Ident::new(&name, self.name.span())
+  }
+}
+
+impl Event {
+  const MODIFIERS: [&'static str; 3] = ["once", "changed", "consume"];
+  const MODIFIERS_WITH_VALUE: [&'static str; 5] = ["delay", "throttle", "from", "target", "queue"];
+
+  /// The `from:` targets that are followed by a CSS selector
+  const RELATIVE_TARGETS: [&'static str; 4] = ["closest", "find", "next", "previous"];
+
+  const QUEUES: [&'static str; 4] = ["first", "last", "all", "none"];
+
+  /// Ensures the modifier of the event only contains valid HTMX trigger
+  /// modifiers, so typos are caught at compile time. The modifier can start
+  /// with an event filter like `[ctrlKey]`, which is returned on its own as
+  /// HTMX expects it right after the name of the event.
+  ///
+  /// The modifier is returned with the selectors of `from:` and `target:`
+  /// that span multiple words wrapped in parentheses, as HTMX would otherwise
+  /// stop reading them at the first space: `from:closest div.a .b` becomes
+  /// `from:(closest div.a .b)`.
+  ///
+  /// Lists of triggers like `from:body, load` are rejected, a declaration is
+  /// for a single event. The listeners of several events are combined in the
+  /// `hx-trigger` attribute instead.
+  fn parse_modifier(modifier: &LitStr) -> syn::Result<(Option<String>, String)> {
+    let value = modifier.value();
+    let error = |message: String| Err(syn::Error::new(modifier.span(), message));
+
+    let (filter, value) = match value.trim_start().strip_prefix('[') {
+      Some(rest) => {
+        let Some(end) = closing_bracket(rest) else {
+          return error("missing `]` after the event filter".to_owned());
+        };
+
+        match rest[..end].trim() {
+          "" => return error("expected a condition inside the `[]` event filter".to_owned()),
+          filter => (Some(filter.to_owned()), &rest[end + 1..])
+        }
+      }
+      None => (None, value.as_str())
+    };
+
+    if has_top_level_comma(value) {
+      return error(
+        "lists of triggers aren't supported in an event declaration, combine the listeners in \
+         the `hx-trigger` attribute instead"
+          .to_owned()
+      );
+    }
+
+    let mut words = value.split_whitespace().peekable();
+    let mut modifiers = Vec::new();
+
+    while let Some(word) = words.next() {
+      if Self::MODIFIERS.contains(&word) {
+        modifiers.push(word.to_owned());
+        continue;
+      }
+
+      if word.starts_with('[') {
+        return error(format!(
+          "the event filter `{word}` must come before the modifiers"
+        ));
+      }
+
+      let Some((name, argument)) = word
+        .split_once(':')
+        .filter(|(name, _)| Self::MODIFIERS_WITH_VALUE.contains(name))
+      else {
+        return error(format!(
+          "unknown trigger modifier `{word}`, expected one of {}, {}",
+          Self::MODIFIERS.join(", "),
+          Self::MODIFIERS_WITH_VALUE
+            .iter()
+            .map(|name| format!("{name}:<value>"))
+            .collect::<Vec<_>>()
+            .join(", ")
+        ));
+      };
+
+      let argument = match name {
+        "delay" | "throttle" if is_duration(argument) => argument.to_owned(),
+        "delay" | "throttle" => {
+          return error(format!(
+            "invalid duration `{argument}` for `{name}:`, expected a number followed by `ms` or `s`"
+          ))
+        }
+        "queue" if Self::QUEUES.contains(&argument) => argument.to_owned(),
+        "queue" => {
+          return error(format!(
+            "invalid queue `{argument}`, expected one of {}",
+            Self::QUEUES.join(", ")
+          ))
+        }
+        // the parenthesised selectors can contain spaces
+        _ if argument.starts_with('(') => {
+          let mut selector = argument.to_owned();
+
+          while !selector.ends_with(')') {
+            match words.next() {
+              Some(word) => selector = format!("{selector} {word}"),
+              None => return error(format!("missing `)` after `{name}:{argument}`"))
+            };
+          }
+
+          if selector[1..selector.len() - 1].trim().is_empty() {
+            return error(format!("expected a selector inside `{name}:()`"));
+          }
+
+          selector
+        }
+        _ => {
+          let mut selector = vec![argument];
+          while let Some(word) = words.next_if(|word| !Self::is_modifier(word)) {
+            selector.push(word);
+          }
+
+          // the relative targets are followed by a selector that HTMX reads
+          // on its own as long as it's a single word
+          let relative = name == "from" && Self::RELATIVE_TARGETS.contains(&argument);
+          let single_word = match relative {
+            true => 2,
+            false => 1
+          };
+
+          match (selector.len(), argument) {
+            (_, "") => return error(format!("expected a selector after `{name}:`")),
+            (1, "closest" | "find") if relative => {
+              return error(format!("expected a selector after `{name}:{argument}`"))
+            }
+            (len, _) if len > single_word => format!("({})", selector.join(" ")),
+            _ => selector.join(" ")
+          }
+        }
+      };
+
+      modifiers.push(format!("{name}:{argument}"));
+    }
+
+    Ok((filter, modifiers.join(" ")))
+  }
+
+  fn is_modifier(word: &str) -> bool {
+    Self::MODIFIERS.contains(&word)
+      || word
+        .split_once(':')
+        .is_some_and(|(name, _)| Self::MODIFIERS_WITH_VALUE.contains(&name))
+  }
+}
+
+/// The position of the `]` closing the filter that starts the value, nested
+/// brackets like `[items[0]]` included.
+fn closing_bracket(value: &str) -> Option<usize> {
+  let mut depth = 1;
+
+  value.char_indices().find_map(|(i, c)| {
+    match c {
+      '[' => depth += 1,
+      ']' => depth -= 1,
+      _ => {}
+    };
+
+    (depth == 0).then_some(i)
+  })
+}
+
+/// Whether the value has a comma outside of the parenthesised selectors
+fn has_top_level_comma(value: &str) -> bool {
+  let mut depth = 0;
+
+  value.chars().any(|c| {
+    match c {
+      '(' => depth += 1,
+      ')' => depth -= 1,
+      _ => {}
+    };
+
+    c == ',' && depth <= 0
+  })
+}
+
+/// Whether the value is a duration HTMX accepts, like `500ms` or `1s`
+fn is_duration(value: &str) -> bool {
+  value
+    .strip_suffix("ms")
+    .or_else(|| value.strip_suffix('s'))
+    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl Parse for Event {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name: Ident = input.parse().map_err(|e| {
+      syn::Error::new(
+        e.span(),
+        "expected an event declaration like `Name \"modifier\"`"
+      )
+    })?;
+
+    let fields = match input.peek(syn::token::Brace) {
+      true => Some(input.parse::<FieldsNamed>()?),
+      false => None
+    };
+
+    let modifier: LitStr = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "event modifier must be quoted"))?;
+    let (filter, parsed) = Self::parse_modifier(&modifier)?;
+    let modifier = LitStr::new(&parsed, modifier.span());
+
+    if input.peek(Token![,]) {
+      input.parse::<Token![,]>()?;
+    }
+
+    Ok(Self {
+      name,
+      fields,
+      filter,
+      modifier
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse_with_filter(modifier: &str) -> syn::Result<(Option<String>, String)> {
+    Event::parse_modifier(&LitStr::new(modifier, proc_macro2::Span::call_site()))
+  }
+
+  fn parse(modifier: &str) -> syn::Result<String> {
+    parse_with_filter(modifier).map(|(_, modifier)| modifier)
+  }
+
+  #[test]
+  fn accepted_modifiers() {
+    for modifier in [
+      "",
+      "once changed consume",
+      "from:body",
+      "from:closest ul",
+      "from:next",
+      "target:.item",
+      "delay:500ms throttle:1s",
+      "queue:last",
+      "from:(closest div.a .b) once"
+    ] {
+      assert_eq!(parse(modifier).unwrap(), modifier);
+    }
+  }
+
+  #[test]
+  fn multi_word_selectors_are_wrapped() {
+    assert_eq!(
+      parse("from:closest div.a .b").unwrap(),
+      "from:(closest div.a .b)"
+    );
+    assert_eq!(
+      parse("target:ul > li delay:1s").unwrap(),
+      "target:(ul > li) delay:1s"
+    );
+    assert_eq!(parse("from:(find  a)").unwrap(), "from:(find a)");
+  }
+
+  #[test]
+  fn rejected_modifiers() {
+    for modifier in [
+      "onse",
+      "from:",
+      "from:closest",
+      "from:(closest div",
+      "from:()",
+      "delay:abc",
+      "delay:500",
+      "throttle:",
+      "delay:ms",
+      "queue:some",
+      "target:",
+      "[ctrlKey",
+      "[]",
+      "once [ctrlKey]"
+    ] {
+      assert!(parse(modifier).is_err(), "`{modifier}` should be rejected");
+    }
+  }
+
+  #[test]
+  fn filters_are_split_from_the_modifiers() {
+    assert_eq!(
+      parse_with_filter("[ctrlKey] from:body").unwrap(),
+      (Some("ctrlKey".to_owned()), "from:body".to_owned())
+    );
+    assert_eq!(
+      parse_with_filter(" [ items[0] && shiftKey ]").unwrap(),
+      (Some("items[0] && shiftKey".to_owned()), "".to_owned())
+    );
+    assert_eq!(
+      parse_with_filter("from:body").unwrap(),
+      (None, "from:body".to_owned())
+    );
+  }
+
+  #[test]
+  fn trigger_lists_are_rejected() {
+    for modifier in ["from:body, load", "once,changed", "[a, b] from:body, load"] {
+      let error = parse(modifier).unwrap_err().to_string();
+      assert!(
+        error.starts_with("lists of triggers aren't supported"),
+        "`{modifier}`: {error}"
+      );
+    }
+
+    // the commas of the filters and of the parenthesised selectors are kept
+    assert_eq!(
+      parse_with_filter("[a(1, 2)] from:(div, span)").unwrap(),
+      (Some("a(1, 2)".to_owned()), "from:(div, span)".to_owned())
+    );
   }
 }
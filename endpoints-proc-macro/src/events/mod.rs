@@ -1,41 +1,73 @@
-use crate::prelude::*;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, Token};
 
 mod field;
 pub use field::Event;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Events {
-  name: String,
+  name: Ident,
+
+  /// The fragment the events belong to, declared with `Events for Fragment`,
+  /// whose ID namespaces the events.
+  fragment: Option<syn::Path>,
   variants: Vec<Event>
 }
 
-impl Events {
-  pub fn parse(i: &str) -> IResult<&str, Self> {
-    let (i, _) = trim(i)?;
-    let (i, name) = take_until1("{")(i)?;
-    let (i, _) = tag("{")(i)?;
-    let (i, variants) = many0(Event::parse)(i)?;
-    let (i, _) = nom::bytes::complete::take_until("}")(i)?;
-
-    Ok((
-      i,
-      Self {
-        variants,
-        name: name.trim().to_owned()
+impl Parse for Events {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name: Ident = input
+      .parse()
+      .map_err(|e| syn::Error::new(e.span(), "expected the name of the events enum"))?;
+
+    let fragment = match input.peek(Token![for]) {
+      true => {
+        input.parse::<Token![for]>()?;
+        Some(input.parse::<syn::Path>()?)
       }
-    ))
+      false => None
+    };
+
+    if !input.peek(syn::token::Brace) {
+      return Err(syn::Error::new(
+        input.span(),
+        "expected `{` after the name of the events enum"
+      ));
+    }
+
+    let content;
+    syn::braced!(content in input);
+
+    let mut variants = Vec::new();
+    while !content.is_empty() {
+      variants.push(content.parse()?);
+    }
+
+    if !input.is_empty() {
+      return Err(input.error("unexpected tokens after the events"));
+    }
+
+    Ok(Self {
+      name,
+      fragment,
+      variants
+    })
   }
+}
 
-  /// Emits a router that combines all of the endpoint routers
+impl Events {
+  /// Emits the enum of the events, with the payload of each event as the
+  /// fields of its variant.
   fn emit_enum(&self) -> proc_macro2::TokenStream {
-    let name = quote::format_ident!("{}", self.name);
+    let name = &self.name;
     let variants: Vec<proc_macro2::TokenStream> = self
       .variants
       .iter()
       .map(|v| {
-        let variant = quote::format_ident!("{}", v.name);
+        let variant = &v.name;
+        let fields = &v.fields;
 
-        quote::quote!(#variant)
+        quote::quote!(#variant #fields)
       })
       .collect();
 
@@ -46,24 +78,124 @@ impl Events {
     )
   }
 
-  fn emit_with_trigger_impl(&self) -> proc_macro2::TokenStream {
-    let name = quote::format_ident!("{}", self.name);
-    let into_trigger_variants: Vec<proc_macro2::TokenStream> = self
+  /// Emits the namespace of the events owned by a fragment, which is the ID of
+  /// the fragment, and ties the events to the fragment so only this fragment
+  /// can emit them.
+  fn emit_namespace(&self) -> proc_macro2::TokenStream {
+    let name = &self.name;
+    let Some(fragment) = &self.fragment else {
+      return quote::quote!();
+    };
+
+    quote::quote!(
+      impl #name {
+        const NAMESPACE: &'static str = {
+          use lv_server::Fragment as _;
+          #fragment::ID
+        };
+      }
+
+      impl lv_server::FragmentEvents for #name {
+        type Fragment = #fragment;
+      }
+    )
+  }
+
+  /// Appends the namespace of the events to the expression if they have one
+  fn namespaced(&self, expr: proc_macro2::TokenStream, method: &str) -> proc_macro2::TokenStream {
+    let method = quote::format_ident!("{}", method);
+
+    match self.fragment {
+      Some(_) => quote::quote!(#expr.#method(Self::NAMESPACE)),
+      None => expr
+    }
+  }
+
+  /// Emits an [EventListener](lv_server::EventListener) constant for each of
+  /// the events.
+  fn emit_listeners(&self) -> proc_macro2::TokenStream {
+    let name = &self.name;
+    let listeners: Vec<proc_macro2::TokenStream> = self
       .variants
       .iter()
       .map(|v| {
-        let variant = quote::format_ident!("{}", v.name);
+        let listener = v.listener_name();
         let event_str = format!("{}_{}", name, v.name);
+        let modifier = v.modifier.value().trim().to_owned();
+        let fields: Vec<String> = v.field_names().iter().map(|f| f.to_string()).collect();
+        let doc = format!("The listener of the [{name}::{}] event", v.name);
+        let filter = v.filter.as_ref().map(|filter| quote::quote!(.filtered(#filter)));
+        let value = self.namespaced(
+          quote::quote!(lv_server::EventListener::new(#event_str, #modifier, &[#(#fields),*]) #filter),
+          "namespaced"
+        );
 
         quote::quote!(
-          Self::#variant => #event_str
+          #[doc = #doc]
+          pub const #listener: lv_server::EventListener<Self> = #value;
         )
       })
       .collect();
 
+    let listen_variants: Vec<proc_macro2::TokenStream> = self
+      .variants
+      .iter()
+      .map(|v| {
+        let variant = &v.name;
+        let listener = v.listener_name();
+
+        quote::quote!(Self::#variant { .. } => Self::#listener.listen())
+      })
+      .collect();
+
+    quote::quote!(
+      impl #name {
+        #(#listeners)*
+
+        /// A [Trigger](lv_server::htmx::Trigger) for the event, without the
+        /// modifiers of its declaration so it can be listened to with others.
+        pub fn listen(&self) -> lv_server::htmx::Trigger {
+          match self {
+            #(#listen_variants),*
+          }
+        }
+      }
+    )
+  }
+
+  fn emit_with_trigger_impl(&self) -> proc_macro2::TokenStream {
+    let name = &self.name;
+    let into_trigger_variants: Vec<proc_macro2::TokenStream> = self
+      .variants
+      .iter()
+      .map(|v| {
+        let variant = &v.name;
+        let event_str = format!("{}_{}", name, v.name);
+        let event = self.namespaced(
+          quote::quote!(lv_server::TriggerEvent::new(#event_str)),
+          "namespace"
+        );
+
+        match &v.fields {
+          None => quote::quote!(
+            Self::#variant => Ok(#event)
+          ),
+          Some(_) => {
+            let fields = v.field_names();
+            let field_strs: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+
+            quote::quote!(
+              Self::#variant { #(#fields),* } => Ok(#event
+                #(.field(#field_strs, &#fields)?)*)
+            )
+          }
+        }
+      })
+      .collect();
+
     quote::quote!(
       impl lv_server::WithTrigger for #name {
-        fn into_trigger(self) -> &'static str {
+        fn into_trigger(self) -> Result<lv_server::TriggerEvent, lv_server::deps::serde_json::Error> {
           match self {
             #(#into_trigger_variants),*
           }
@@ -73,16 +205,31 @@ impl Events {
   }
 
   fn emit_maud_render_impl(&self) -> proc_macro2::TokenStream {
-    let name = quote::format_ident!("{}", self.name);
+    let name = &self.name;
+    let namespace = match self.fragment {
+      Some(_) => quote::quote!(
+        buffer.push_str(Self::NAMESPACE);
+        buffer.push(':');
+      ),
+      None => quote::quote!()
+    };
     let render_variants: Vec<proc_macro2::TokenStream> = self
       .variants
       .iter()
       .map(|v| {
-        let variant = quote::format_ident!("{}", v.name);
-        let event_str = format!("{}_{} {}", name, v.name, v.modifier);
+        let variant = &v.name;
+        let mut event_str = format!("{}_{}", name, v.name);
+        if let Some(filter) = &v.filter {
+          event_str.push_str(&format!("[{filter}]"));
+        }
+
+        let modifier = v.modifier.value();
+        if !modifier.trim().is_empty() {
+          event_str.push_str(&format!(" {}", modifier.trim()));
+        }
 
         quote::quote!(
-          Self::#variant => buffer.push_str(#event_str)
+          Self::#variant { .. } => buffer.push_str(#event_str)
         )
       })
       .collect();
@@ -90,6 +237,7 @@ impl Events {
     quote::quote!(
       impl maud::Render for #name {
         fn render_to(&self, buffer: &mut String) {
+          #namespace
           match self {
             #(#render_variants),*
           };
@@ -97,20 +245,20 @@ impl Events {
       }
     )
   }
-}
 
-impl std::fmt::Display for Events {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+  pub fn emit(&self) -> proc_macro2::TokenStream {
     let enum_def = self.emit_enum();
+    let enum_namespace = self.emit_namespace();
+    let enum_listeners = self.emit_listeners();
     let enum_with_trigger_impl = self.emit_with_trigger_impl();
     let enum_maud_render_impl = self.emit_maud_render_impl();
 
-    let output = quote::quote! {
+    quote::quote! {
       #enum_def
+      #enum_namespace
+      #enum_listeners
       #enum_with_trigger_impl
       #enum_maud_render_impl
-    };
-
-    write!(f, "{output}")
+    }
   }
 }
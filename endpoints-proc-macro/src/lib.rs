@@ -18,19 +18,21 @@ mod endpoints;
 ///   post_create_form => POST "create"
 ///   post_edit_form extend(extend_config_limit) => POST "{account_id}/{project_slug}"
 ///   delete_project => DELETE "{account_id}/{project_slug}"
+///
+///   // any method, or custom ones:
+///   any_project => ANY "{account_id}/{project_slug}/any"
+///   purge_project => METHOD("PURGE") "{account_id}/{project_slug}"
 /// });
 /// ```
 #[proc_macro]
 pub fn endpoints(input: TokenStream) -> TokenStream {
-  let content = input.to_string();
-  let (_, model) = endpoints::Router::parse(&content).unwrap_or_default();
-  let output = model.to_string();
+  let model = syn::parse_macro_input!(input as endpoints::Router);
+  let output = model.emit();
 
   // use the following to debug outputs
   // eprintln!("{output}");
 
-  use std::str::FromStr;
-  TokenStream::from_str(&output).unwrap_or_default()
+  output.into()
 }
 
 mod events;
@@ -41,6 +43,15 @@ mod events;
 /// ```rs
 /// lv_server::events!(ProjectEditFormsEvents {
 ///   Reload "from:body"
+///   Updated { id: u64 } "from:body"
+/// });
+/// ```
+///
+/// The events of a fragment are declared for it, so they are namespaced with
+/// its `ID` everywhere they are sent and rendered:
+/// ```rs
+/// lv_server::events!(ProjectEditFormsEvents for ProjectEditForms {
+///   Reload "from:body"
 /// });
 /// ```
 ///
@@ -49,9 +60,15 @@ mod events;
 /// ```rs
 /// div
 ///   hx-trigger={(ProjectEditFormsEvents::Reload)}
-///   hx-get={(api::get_index::url())}
+///   hx-get=(api::get_index::hx_get())
 ///   hx-target="this"
 ///   {"This div sends a GET request on this event"}
+///
+/// div
+///   hx-trigger=(ProjectEditFormsEvents::UPDATED)
+///   hx-vals=(ProjectEditFormsEvents::UPDATED.vals())
+///   hx-get=(api::get_project::hx_get())
+///   {"This div sends the id of the updated project along its request"}
 /// ```
 ///
 ///
@@ -67,25 +84,11 @@ mod events;
 /// ```
 #[proc_macro]
 pub fn events(input: TokenStream) -> TokenStream {
-  let content = input.to_string();
-  let (_, events) = events::Events::parse(&content).unwrap_or_default();
-  let output = events.to_string();
+  let events = syn::parse_macro_input!(input as events::Events);
+  let output = events.emit();
 
   // use the following to debug outputs
   // eprintln!("{output}");
 
-  use std::str::FromStr;
-  TokenStream::from_str(&output).unwrap_or_default()
-}
-
-mod prelude {
-
-  pub use nom::bytes::complete::{tag, take_until1, take_while, take_while1};
-  pub use nom::error::ParseError;
-  pub use nom::multi::many0;
-  pub use nom::IResult;
-
-  pub fn trim(i: &str) -> IResult<&str, &str> {
-    take_while(|c| c == ' ' || c == '\n' || c == '\r')(i)
-  }
+  output.into()
 }
@@ -4,6 +4,7 @@ pub type ApiData = lv_server::deps::actix_web::web::Data<AppData>;
 
 /// A shared mutable state to simulate a database, the implementation is far from
 /// efficient, lots of cloning is happening to keep the code simple.
+#[derive(Default)]
 pub struct AppData {
   todos: Mutex<Vec<Todo>>
 }
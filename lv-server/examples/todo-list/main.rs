@@ -16,10 +16,24 @@ async fn main() {
   println!("running server on http://localhost:{port}");
 
   let app_data = actix_web::web::Data::new(app_data::AppData::new());
+  let hub = lv_server::sse::Hub::new();
+
+  // enables the token mode of the CSRF protection, the secret should come from
+  // the configuration of the server rather than being generated on each start.
+  let csrf_tokens = lv_server::csrf::CsrfTokens::generate();
+
+  // the user sessions, the CSRF tokens are then bound to them
+  let sessions = lv_server::session::Sessions::new(lv_server::session::MemoryStore::new());
 
   HttpServer::new(move || {
     App::new()
       .app_data(actix_web::web::Data::clone(&app_data))
+      .app_data(hub.clone())
+      .app_data(csrf_tokens.clone())
+      .app_data(sessions.clone())
+      .wrap(actix_web::middleware::from_fn(
+        lv_server::session::middleware
+      ))
       .configure(routes)
   })
   .bind(format!("127.0.0.1:{}", port))
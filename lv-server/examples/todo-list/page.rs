@@ -2,18 +2,20 @@ use crate::prelude::*;
 
 /// Main template for a complete HTML page, uses the provided [Markup] as the body.
 /// The header and footers are automatically added around that body.
-pub fn page(content: Markup) -> Markup {
+pub fn page(content: Markup, csrf: &CsrfToken) -> Markup {
   html!(
     (maud::DOCTYPE)
     html lang="en"
       // one of the many layers to protect against CSRF, this one is mandatory
-      // in order to sent any request that's not a GET towards a fragment.
-      hx-headers={"{\"X-LVSERVER-REQ\": \""(nanoid::nanoid!())"\"}"}
+      // in order to sent any request that's not a GET towards a fragment. The
+      // token is signed by the server and bound to the session cookie.
+      hx-headers=(csrf.hx_headers())
     {
       head {
         meta charset="utf-8";
         title { "lv_server" }
         script type="text/javascript" src="/static/htmx.min.js" {}
+        script type="text/javascript" src="https://unpkg.com/htmx-ext-sse@2.2.2/sse.js" {}
         link rel="stylesheet" href="/static/style.css";
         meta name="htmx-config" content="{\"defaultSwapStyle\":\"outerHTML\", \"selfRequestsOnly\": true}";
       }
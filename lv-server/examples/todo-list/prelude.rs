@@ -4,7 +4,11 @@ pub use actix_web::web;
 pub use actix_web::web::Form;
 pub use actix_web::HttpResponse;
 
+pub use lv_server::csrf::CsrfToken;
+pub use lv_server::htmx::{HxRequest, Swap, Target, Trigger};
+pub use lv_server::sse::{self, Hub};
 pub use lv_server::ExtMaudMarkup;
+pub use lv_server::Fragment;
 
 pub use maud::html;
 pub use maud::Markup;
@@ -16,7 +16,9 @@ pub struct PostAddTodoForm {
 }
 
 impl api::post_add_todo::Router {
-  pub async fn endpoint(Form(form): Form<PostAddTodoForm>, data: ApiData) -> HttpResponse {
+  pub async fn endpoint(
+    Form(form): Form<PostAddTodoForm>, data: ApiData, hub: Hub
+  ) -> HttpResponse {
     if form.text.trim().is_empty() {
       return AddTodoForm::render()
         .join(lv_server::responses::alert(
@@ -28,7 +30,11 @@ impl api::post_add_todo::Router {
 
     data.add_todo(form.text);
 
-    AddTodoForm::render().into_response_with_event(super::TodoListEvents::Reload)
+    // the other tabs are notified through the SSE stream
+    hub.publish_event(super::TodoList::emit(super::TodoListEvents::Reload));
+
+    AddTodoForm::render()
+      .into_response_with_event(super::TodoList::emit(super::TodoListEvents::Reload))
   }
 }
 
@@ -36,11 +42,11 @@ impl AddTodoForm {
   pub fn render() -> Markup {
     html!(
       form.fdn.row
-        hx-post={(api::post_add_todo::url())}
-        hx-target="this"
-        hx-swap="outerHTML"
+        hx-post=(api::post_add_todo::hx_post())
+        hx-target=(Target::This)
+        hx-swap=(Swap::OuterHtml)
       {
-        input name="text" placeholder="Todo's text" {}
+        input name="text" placeholder="Todo's text";
         input type="submit" value="Add";
       }
     )
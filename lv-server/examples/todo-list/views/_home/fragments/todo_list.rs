@@ -1,10 +1,8 @@
-use actix_web::web::Path;
-
 use crate::prelude::*;
 
 pub struct TodoList;
 
-lv_server::events!(TodoListEvents {
+lv_server::events!(TodoListEvents for TodoList {
   Reload "from:body"
 });
 
@@ -13,57 +11,55 @@ impl lv_server::Fragment<TodoListEvents, api::Router> for TodoList {
 }
 
 lv_server::endpoints!(TodoList {
-  get_index => GET "/"
-  get_todo => GET "/todos/{index}"
+  get_index => GET "/" ?(page: Option<usize>)
+  get_todo => GET "/todos/{index: usize}"
 
-  delete_todo => DELETE "/todos/{index}"
+  delete_todo => DELETE "/todos/{index: usize}"
 
-  get_edit_form => GET "/todos/{index}/edit"
-  post_update_todo => POST "/todos/{index}"
+  // renders the edit form on GET, and updates the todo once it's submitted
+  edit_todo => GET | POST "/todos/{index: usize}/edit"
 });
 
 impl api::get_index::Router {
-  pub async fn endpoint(data: ApiData) -> HttpResponse {
-    TodoList::render(&data.todos()).into_response()
+  pub async fn endpoint(query: api::get_index::Query, data: ApiData) -> HttpResponse {
+    TodoList::render(&data.todos(), query.page.unwrap_or_default()).into_response()
   }
 }
 
 impl api::delete_todo::Router {
-  pub async fn endpoint(path: Path<usize>, data: ApiData) -> HttpResponse {
-    data.remove_todo_by_index(path.into_inner());
+  pub async fn endpoint(path: api::delete_todo::Path, data: ApiData) -> HttpResponse {
+    data.remove_todo_by_index(path.index);
 
-    TodoList::render(&data.todos()).into_response()
+    TodoList::render(&data.todos(), 0).into_response()
   }
 }
 
 impl api::get_todo::Router {
-  pub async fn endpoint(path: Path<usize>, data: ApiData) -> HttpResponse {
-    let index = path.into_inner();
+  pub async fn endpoint(path: api::get_todo::Path, data: ApiData) -> HttpResponse {
+    let index = path.index;
     let todo = data.todos().remove(index);
 
     TodoList::render_todo_item(&todo, index).into_response()
   }
 }
 
-impl api::get_edit_form::Router {
-  pub async fn endpoint(path: Path<usize>, data: ApiData) -> HttpResponse {
-    let index = path.into_inner();
-    let todo = data.todos().remove(index);
-
-    TodoList::render_todo_edit_form(&todo, index).into_response()
-  }
-}
-
 #[derive(Deserialize)]
 pub struct PostUpdateTodoForm {
   text: String
 }
 
-impl api::post_update_todo::Router {
-  pub async fn endpoint(
-    path: Path<usize>, Form(form): Form<PostUpdateTodoForm>, data: ApiData
+impl api::edit_todo::Router {
+  pub async fn get(path: api::edit_todo::Path, data: ApiData) -> HttpResponse {
+    let index = path.index;
+    let todo = data.todos().remove(index);
+
+    TodoList::render_todo_edit_form(&todo, index).into_response()
+  }
+
+  pub async fn post(
+    path: api::edit_todo::Path, Form(form): Form<PostUpdateTodoForm>, data: ApiData
   ) -> HttpResponse {
-    let index = path.into_inner();
+    let index = path.index;
     let todo = data.update_todo_by_index(index, form.text);
 
     TodoList::render_todo_item(&todo, index)
@@ -73,38 +69,57 @@ impl api::post_update_todo::Router {
 }
 
 impl TodoList {
-  pub fn render(todos: &Vec<Todo>) -> Markup {
-    html!(
+  const PAGE_SIZE: usize = 5;
+
+  pub fn render(todos: &[Todo], page: usize) -> Markup {
+    let page_url = |page: usize| {
+      // a number always fits in a query string, the fallback is never used
+      api::get_index::url_with_query(&api::get_index::QueryParams { page: Some(page) })
+        .unwrap_or_else(|_| api::get_index::url())
+    };
+
+    Self::root(html!(
       .fdn.block.col
-        hx-trigger={(TodoListEvents::Reload)}
-        hx-get={(api::get_index::url())}
-        hx-target="this"
+        hx-trigger={
+          (TodoList::listen(TodoListEvents::RELOAD)) ", "
+          (sse::listen(TodoListEvents::RELOAD))
+        }
+        hx-get={(page_url(page))}
+        hx-target=(TodoList::target())
       {
         .fdn.title {"Your todos"}
         ul.fdn.col {
-          @for (index, todo) in todos.iter().enumerate() {
+          @for (index, todo) in todos.iter().enumerate().skip(page * Self::PAGE_SIZE).take(Self::PAGE_SIZE) {
             (Self::render_todo_item(todo, index))
           }
         }
+        .fdn.row {
+          @if page > 0 {
+            button hx-get={(page_url(page - 1))} {"previous"}
+          }
+          @if (page + 1) * Self::PAGE_SIZE < todos.len() {
+            button hx-get={(page_url(page + 1))} {"next"}
+          }
+        }
       }
-    )
+    ))
   }
 
   fn render_todo_item(todo: &Todo, index: usize) -> Markup {
     html!(
-      li.fdn.row.items-center
+      li.fdn.row.items-center id=(TodoList::child_id("item", index))
       {
         (todo.text)
 
         button
-          hx-delete={(api::delete_todo::url(&index.to_string()))}
+          hx-delete=(api::delete_todo::hx_delete(index))
           hx-confirm={"Delete todo '"(todo.text)"'?"}
           {"X"}
 
         button
-          hx-get={(api::get_edit_form::url(&index.to_string()))}
-          hx-target="closest li"
-          hx-swap="outerHTML"
+          hx-get=(api::edit_todo::hx_get(index))
+          hx-target=(TodoList::child_target("item", index))
+          hx-swap=(Swap::OuterHtml)
           {"✏️"}
       }
     )
@@ -113,15 +128,13 @@ impl TodoList {
   fn render_todo_edit_form(todo: &Todo, index: usize) -> Markup {
     html!(
       form
-        hx-post={(api::post_update_todo::url(&index.to_string()))}
-        hx-target="this"
-        hx-swap="outerHTML"
+        hx-post=(api::edit_todo::hx_post(index))
+        hx-target=(Target::This)
+        hx-swap=(Swap::OuterHtml)
       {
         input name="text" value={(todo.text)};
 
-        button
-          hx-get={(api::get_todo::url(&index.to_string()))}
-          {"cancel"}
+        button hx-get=(api::get_todo::hx_get(index)) {"cancel"}
 
         input type="submit" value="save";
       }
@@ -8,20 +8,35 @@ impl lv_server::View<(fragments::TodoList, fragments::AddTodoForm)> for ViewHome
 
 lv_server::endpoints!(ViewHome as view {
   get_index => GET "/"
+
+  // pushes the updates made in other tabs
+  stream => SSE "/stream"
 });
 
 impl api::get_index::Router {
-  async fn endpoint(data: ApiData) -> HttpResponse {
-    page(ViewHome::render(data)).into_response()
+  async fn endpoint(hx: HxRequest, csrf: CsrfToken, data: ApiData) -> HttpResponse {
+    // HTMX requests only need the content, while direct loads need the whole page
+    match hx.is_partial() {
+      true => ViewHome::render(data).into_response(),
+      false => csrf.set_cookie(page(ViewHome::render(data), &csrf).into_response())
+    }
+  }
+}
+
+impl api::stream::Router {
+  async fn endpoint(hub: Hub) -> HttpResponse {
+    hub.response()
   }
 }
 
 impl ViewHome {
   fn render(data: ApiData) -> Markup {
     html!(
-      .fdn.col.justify-center.items.center {
-        (fragments::TodoList::render(&data.todos()))
-        (fragments::AddTodoForm::render())
+      div hx-ext="sse" sse-connect=(api::stream::sse_connect()) {
+        .fdn.col.justify-center.items.center {
+          (fragments::TodoList::render(&data.todos(), 0))
+          (fragments::AddTodoForm::render())
+        }
       }
     )
   }
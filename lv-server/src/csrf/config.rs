@@ -0,0 +1,289 @@
+use std::sync::OnceLock;
+
+use actix_web::HttpRequest;
+
+use super::{origin, CsrfError};
+
+/// The policy of the CSRF protection, the defaults are used unless a config is
+/// added to the app data:
+/// ```rs
+/// App::new().app_data(
+///   CsrfConfig::new()
+///     .trusted_origin("https://example.com")
+///     .exempt("/webhooks/*")
+/// )
+/// ```
+///
+/// The site of a request is verified with its `Sec-Fetch-Site` header, and
+/// with its origin from the `Origin` or `Referer` headers for the clients that
+/// don't send it. An origin is accepted if it has the scheme, the host and the
+/// port of the request or if it's one of the
+/// [trusted origins](CsrfConfig::trusted_origin).
+#[derive(Debug, Clone)]
+pub struct CsrfConfig {
+  header_name: String,
+  fetch_sites: Vec<String>,
+  trusted_origins: Vec<String>,
+  exemptions: Vec<String>,
+  error_fragment: Option<fn(&CsrfError) -> maud::Markup>,
+  require_origin: bool,
+  forwarded_headers: bool
+}
+
+impl Default for CsrfConfig {
+  fn default() -> Self {
+    Self {
+      header_name: "X-LVSERVER-REQ".to_owned(),
+      fetch_sites: vec!["same-origin".to_owned(), "same-site".to_owned()],
+      trusted_origins: Vec::new(),
+      exemptions: Vec::new(),
+      error_fragment: None,
+      require_origin: false,
+      forwarded_headers: false
+    }
+  }
+}
+
+impl CsrfConfig {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// The header the requests must include, `X-LVSERVER-REQ` by default
+  pub fn header_name(mut self, name: impl Into<String>) -> Self {
+    self.header_name = name.into();
+    self
+  }
+
+  /// The values of the `Sec-Fetch-Site` header that are accepted,
+  /// `same-origin` and `same-site` by default.
+  pub fn allowed_fetch_sites(mut self, sites: impl IntoIterator<Item = impl Into<String>>) -> Self {
+    self.fetch_sites = sites.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// An origin the requests are accepted from on top of the host of the
+  /// request, in the `scheme://host[:port]` format.
+  pub fn trusted_origin(mut self, origin: impl Into<String>) -> Self {
+    let origin: String = origin.into();
+    self
+      .trusted_origins
+      .push(origin.trim_end_matches('/').to_owned());
+    self
+  }
+
+  /// A path that isn't subject to the CSRF checks, like the endpoints of a
+  /// webhook. A trailing `*` exempts every path that starts with the prefix.
+  pub fn exempt(mut self, path: impl Into<String>) -> Self {
+    self.exemptions.push(path.into());
+    self
+  }
+
+  /// Whether the origin of the request must also be accepted when its
+  /// `Sec-Fetch-Site` header is, disabled by default.
+  pub fn require_origin(mut self, require: bool) -> Self {
+    self.require_origin = require;
+    self
+  }
+
+  /// Whether the scheme and the host of the request are read from the
+  /// `Forwarded`, `X-Forwarded-Proto` and `X-Forwarded-Host` headers, disabled
+  /// by default. It should only be enabled behind a proxy that sets them, and
+  /// is needed behind the ones that terminate the TLS connections as the
+  /// `https` origins wouldn't match the scheme of the request otherwise.
+  pub fn trust_forwarded_headers(mut self, trust: bool) -> Self {
+    self.forwarded_headers = trust;
+    self
+  }
+
+  /// The markup sent to the HTMX requests that fail the CSRF checks, instead
+  /// of the plain text reason:
+  /// ```rs
+  /// CsrfConfig::new().error_fragment(|error| responses::alert("error", &error.to_string()))
+  /// ```
+  ///
+  /// Note that HTMX doesn't swap the `403` responses unless its
+  /// [responseHandling](https://htmx.org/docs/#response-handling) config says so.
+  pub fn error_fragment(mut self, fragment: fn(&CsrfError) -> maud::Markup) -> Self {
+    self.error_fragment = Some(fragment);
+    self
+  }
+
+  pub fn get_header_name(&self) -> &str {
+    &self.header_name
+  }
+
+  pub fn get_error_fragment(&self) -> Option<fn(&CsrfError) -> maud::Markup> {
+    self.error_fragment
+  }
+
+  pub fn is_exempt(&self, path: &str) -> bool {
+    self
+      .exemptions
+      .iter()
+      .any(|exemption| match exemption.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => path == exemption
+      })
+  }
+
+  /// Whether the request comes from an allowed site. A `Sec-Fetch-Site` header
+  /// that isn't allowed is always rejected, the origin is only verified when
+  /// the header is missing or when the config requires it.
+  pub(super) fn verify_site(&self, req: &HttpRequest) -> Result<(), CsrfError> {
+    let fetch_site = req
+      .headers()
+      .get("Sec-Fetch-Site")
+      .and_then(|value| value.to_str().ok());
+
+    match fetch_site {
+      Some(site) if !self.fetch_sites.iter().any(|allowed| allowed == site) => {
+        Err(CsrfError::CrossSite)
+      }
+      Some(_) if !self.require_origin => Ok(()),
+      _ => match origin::request_origin(req.headers()) {
+        Some(origin) if self.is_trusted_origin(req, origin) => Ok(()),
+        Some(_) => Err(CsrfError::CrossSite),
+        None => Err(CsrfError::UnknownSite)
+      }
+    }
+  }
+
+  fn is_trusted_origin(&self, req: &HttpRequest, origin: &str) -> bool {
+    let scheme = origin::request_scheme(req, self.forwarded_headers);
+    let is_same_origin = origin::request_host(req, self.forwarded_headers)
+      .is_some_and(|host| origin::is_same_origin(origin, scheme, host));
+
+    is_same_origin
+      || self
+        .trusted_origins
+        .iter()
+        .any(|trusted| trusted.eq_ignore_ascii_case(origin))
+  }
+}
+
+/// The config of the app, or the default one if it doesn't have any
+pub(super) fn config(config: Option<&CsrfConfig>) -> &CsrfConfig {
+  static DEFAULT: OnceLock<CsrfConfig> = OnceLock::new();
+
+  config.unwrap_or_else(|| DEFAULT.get_or_init(CsrfConfig::default))
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::test::TestRequest;
+
+  use super::*;
+
+  fn request(headers: &[(&str, &str)]) -> HttpRequest {
+    headers
+      .iter()
+      .fold(
+        TestRequest::default().insert_header(("Host", "example.com")),
+        |req, header| req.insert_header(*header)
+      )
+      .to_http_request()
+  }
+
+  #[test]
+  fn fetch_site() {
+    let config = CsrfConfig::new();
+
+    assert_eq!(
+      config.verify_site(&request(&[("Sec-Fetch-Site", "same-origin")])),
+      Ok(())
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Sec-Fetch-Site", "cross-site")])),
+      Err(CsrfError::CrossSite)
+    );
+  }
+
+  #[test]
+  fn trusted_origin_does_not_override_cross_site() {
+    let config = CsrfConfig::new().trusted_origin("https://trusted.com");
+
+    for origin in ["http://example.com", "https://trusted.com"] {
+      let req = request(&[("Sec-Fetch-Site", "cross-site"), ("Origin", origin)]);
+      assert_eq!(config.verify_site(&req), Err(CsrfError::CrossSite));
+    }
+  }
+
+  #[test]
+  fn origin_without_fetch_site() {
+    let config = CsrfConfig::new().trusted_origin("https://trusted.com/");
+
+    assert_eq!(
+      config.verify_site(&request(&[("Origin", "http://example.com")])),
+      Ok(())
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Origin", "https://trusted.com")])),
+      Ok(())
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Referer", "http://example.com/todos?page=2")])),
+      Ok(())
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Origin", "https://example.com")])),
+      Err(CsrfError::CrossSite)
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Origin", "http://example.com:8080")])),
+      Err(CsrfError::CrossSite)
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Origin", "null")])),
+      Err(CsrfError::UnknownSite)
+    );
+    assert_eq!(
+      config.verify_site(&request(&[])),
+      Err(CsrfError::UnknownSite)
+    );
+  }
+
+  #[test]
+  fn required_origin() {
+    let config = CsrfConfig::new().require_origin(true);
+
+    assert_eq!(
+      config.verify_site(&request(&[
+        ("Sec-Fetch-Site", "same-origin"),
+        ("Origin", "http://example.com")
+      ])),
+      Ok(())
+    );
+    assert_eq!(
+      config.verify_site(&request(&[
+        ("Sec-Fetch-Site", "same-origin"),
+        ("Origin", "http://other.com")
+      ])),
+      Err(CsrfError::CrossSite)
+    );
+    assert_eq!(
+      config.verify_site(&request(&[("Sec-Fetch-Site", "same-origin")])),
+      Err(CsrfError::UnknownSite)
+    );
+  }
+
+  #[test]
+  fn forwarded_headers() {
+    let headers = [
+      ("X-Forwarded-Host", "app.com, proxy.internal"),
+      ("X-Forwarded-Proto", "https"),
+      ("Origin", "https://app.com")
+    ];
+
+    assert_eq!(
+      CsrfConfig::new().verify_site(&request(&headers)),
+      Err(CsrfError::CrossSite)
+    );
+    assert_eq!(
+      CsrfConfig::new()
+        .trust_forwarded_headers(true)
+        .verify_site(&request(&headers)),
+      Ok(())
+    );
+  }
+}
@@ -0,0 +1,48 @@
+/// The reason a request failed the CSRF checks, the rejected requests get a
+/// `403 Forbidden` response with the reason as its body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrfError {
+  /// The request doesn't include the CSRF header
+  MissingHeader,
+  /// The `Sec-Fetch-Site` header or the origin of the request isn't allowed
+  CrossSite,
+  /// The request has neither a `Sec-Fetch-Site` header nor an origin, or no
+  /// origin while the config requires one
+  UnknownSite,
+  /// In the token mode, the token wasn't issued for the session of the request
+  /// or it has expired
+  InvalidToken
+}
+
+impl CsrfError {
+  /// The name of the rule that failed, as it appears in the logs
+  pub fn rule(&self) -> &'static str {
+    match self {
+      CsrfError::MissingHeader => "missing-header",
+      CsrfError::CrossSite => "cross-site",
+      CsrfError::UnknownSite => "unknown-site",
+      CsrfError::InvalidToken => "invalid-token"
+    }
+  }
+}
+
+impl std::fmt::Display for CsrfError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let reason = match self {
+      CsrfError::MissingHeader => "the request is missing the CSRF header",
+      CsrfError::CrossSite => "cross-site requests are not allowed",
+      CsrfError::UnknownSite => "the site the request comes from is unknown",
+      CsrfError::InvalidToken => "the CSRF token is invalid or has expired"
+    };
+
+    write!(f, "CSRF check failed: {reason}")
+  }
+}
+
+impl std::error::Error for CsrfError {}
+
+impl actix_web::ResponseError for CsrfError {
+  fn status_code(&self) -> actix_web::http::StatusCode {
+    actix_web::http::StatusCode::FORBIDDEN
+  }
+}
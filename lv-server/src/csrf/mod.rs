@@ -0,0 +1,238 @@
+/// A middleware that is added to all endpoints from the [`endpoints!`] macro.
+/// It performs basic checks on the methods that aren't [safe](is_safe_method)
+/// to eliminate the most unsafe requests and ensure out of-the-box CSRF
+/// protection. The requests that fail the checks get a `403 Forbidden`
+/// response, refer to [CsrfError].
+///
+/// The route must already have its handler since actix drops the middlewares
+/// of the routes whose handler is set afterwards.
+pub fn csrf_protection(
+  route: actix_web::Route, method: actix_web::http::Method
+) -> actix_web::Route {
+  match is_safe_method(&method) {
+    true => route,
+    false => csrf_middleware(route)
+  }
+}
+
+/// Same as [csrf_protection] but for the routes that accept any method, the
+/// checks are then performed on every request whose method isn't safe.
+pub fn csrf_protection_any(route: actix_web::Route) -> actix_web::Route {
+  csrf_middleware(route)
+}
+
+/// GET, HEAD and OPTIONS requests are not supposed to have side effects and are
+/// therefore not subject to the CSRF checks.
+pub fn is_safe_method(method: &actix_web::http::Method) -> bool {
+  use actix_web::http::Method;
+
+  matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Whether the request passes the CSRF checks, either the token mode ones if
+/// [CsrfTokens] are in the app data or the default ones. Both follow the
+/// [CsrfConfig] of the app data.
+pub fn is_csrf_safe(req: &actix_web::HttpRequest) -> bool {
+  verify(req).is_ok()
+}
+
+/// Same as [is_csrf_safe] but with the reason the request failed the checks
+pub fn verify(req: &actix_web::HttpRequest) -> Result<(), CsrfError> {
+  verify_request(
+    req,
+    config::config(req.app_data()),
+    req.app_data::<CsrfTokens>()
+  )
+}
+
+/// Browsers can't add custom headers to WebSocket handshakes, only the origin
+/// of the request is verified and the CSRF header is expected in each message
+/// instead, refer to [is_ws_message_safe].
+pub fn is_ws_handshake_safe(req: &actix_web::HttpRequest) -> bool {
+  let config = config::config(req.app_data());
+  let site = config.verify_site(req);
+
+  match req.app_data::<CsrfTokens>() {
+    _ if config.is_exempt(req.path()) => true,
+    Some(_) => site != Err(CsrfError::CrossSite),
+    None => site.is_ok()
+  }
+}
+
+/// Verifies the CSRF header is part of the `HEADERS` object the HTMX WebSocket
+/// extension adds to the messages it sends, in the token mode the token must
+/// also be bound to the session of the handshake request.
+pub fn is_ws_message_safe(req: &actix_web::HttpRequest, headers: &serde_json::Value) -> bool {
+  let config = config::config(req.app_data());
+  let token = headers.get(config.get_header_name());
+
+  match req.app_data::<CsrfTokens>() {
+    _ if config.is_exempt(req.path()) => true,
+    Some(tokens) => match (token.and_then(|t| t.as_str()), tokens.session(req)) {
+      (Some(token), Some(session)) => tokens.verify(token, &session),
+      _ => false
+    },
+    None => token.is_some()
+  }
+}
+
+mod config;
+mod error;
+mod origin;
+mod token;
+pub use config::CsrfConfig;
+pub use error::CsrfError;
+pub use token::{CsrfToken, CsrfTokens};
+
+fn verify_request(
+  req: &actix_web::HttpRequest, config: &CsrfConfig, tokens: Option<&CsrfTokens>
+) -> Result<(), CsrfError> {
+  let header_name = config.get_header_name();
+  let head = req.head();
+  let headers = head.headers();
+
+  if config.is_exempt(head.uri.path()) {
+    return Ok(());
+  }
+
+  if !headers.contains_key(header_name) {
+    return Err(CsrfError::MissingHeader);
+  }
+
+  match tokens {
+    // the token can't be forged, the site only has to not be a foreign one
+    Some(tokens) => match config.verify_site(req) {
+      Err(CsrfError::CrossSite) => Err(CsrfError::CrossSite),
+      _ if !tokens.verify_request(req, header_name) => Err(CsrfError::InvalidToken),
+      _ => Ok(())
+    },
+    None => config.verify_site(req)
+  }
+}
+
+fn csrf_middleware(route: actix_web::Route) -> actix_web::Route {
+  route.wrap(actix_web::middleware::from_fn(csrf_check))
+}
+
+async fn csrf_check(
+  req: actix_web::dev::ServiceRequest, next: actix_web::middleware::Next<actix_web::body::BoxBody>
+) -> Result<actix_web::dev::ServiceResponse, actix_web::Error> {
+  if is_safe_method(req.method()) {
+    return next.call(req).await;
+  }
+
+  let Err(error) = verify(req.request()) else {
+    return next.call(req).await;
+  };
+
+  log::debug!(
+    "CSRF check `{}` failed for {} {}: {error}",
+    error.rule(),
+    req.method(),
+    req.path()
+  );
+
+  let config = config::config(req.app_data());
+  let is_htmx = req.headers().contains_key("HX-Request");
+  let res = match config.get_error_fragment() {
+    Some(fragment) if is_htmx => actix_web::HttpResponse::Forbidden()
+      .content_type("text/html; charset=utf-8")
+      .body(fragment(&error).into_string()),
+    _ => actix_web::ResponseError::error_response(&error)
+  };
+
+  Ok(req.into_response(res))
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::http::{Method, StatusCode};
+  use actix_web::test::{self, TestRequest};
+  use actix_web::{web, App, HttpResponse};
+
+  use super::*;
+
+  fn error_fragment(error: &CsrfError) -> maud::Markup {
+    maud::html!(p.error { (error.rule()) })
+  }
+
+  macro_rules! app {
+    ($config:expr) => {
+      test::init_service(App::new().app_data($config).route(
+        "/{path:.*}",
+        csrf_protection(
+          web::post().to(|| async { HttpResponse::Ok().body("saved") }),
+          Method::POST
+        )
+      ))
+      .await
+    };
+  }
+
+  #[actix_web::test]
+  async fn rejected_requests_get_the_reason() {
+    let app = app!(CsrfConfig::new());
+    let req = TestRequest::post()
+      .uri("/todos")
+      .insert_header(("Sec-Fetch-Site", "same-origin"))
+      .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+      test::read_body(res).await,
+      "CSRF check failed: the request is missing the CSRF header"
+    );
+  }
+
+  #[actix_web::test]
+  async fn rejected_htmx_requests_get_the_error_fragment() {
+    let app = app!(CsrfConfig::new().error_fragment(error_fragment));
+    let req = TestRequest::post()
+      .uri("/todos")
+      .insert_header(("HX-Request", "true"))
+      .insert_header(("X-LVSERVER-REQ", "1"))
+      .insert_header(("Sec-Fetch-Site", "cross-site"))
+      .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+      res.headers().get("Content-Type").unwrap(),
+      "text/html; charset=utf-8"
+    );
+    assert_eq!(
+      test::read_body(res).await,
+      r#"<p class="error">cross-site</p>"#
+    );
+  }
+
+  #[actix_web::test]
+  async fn same_origin_requests_are_accepted() {
+    let app = app!(CsrfConfig::new().error_fragment(error_fragment));
+    let req = TestRequest::post()
+      .uri("/todos")
+      .insert_header(("Host", "example.com"))
+      .insert_header(("X-LVSERVER-REQ", "1"))
+      .insert_header(("Sec-Fetch-Site", "same-origin"))
+      .insert_header(("Origin", "http://example.com"))
+      .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(test::read_body(res).await, "saved");
+  }
+
+  #[actix_web::test]
+  async fn exempt_paths_are_not_checked() {
+    let app = app!(CsrfConfig::new().exempt("/webhooks/*"));
+
+    let req = TestRequest::post().uri("/webhooks/stripe").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = TestRequest::post().uri("/todos").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+  }
+}
@@ -0,0 +1,231 @@
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::HttpRequest;
+
+/// The origin of the request from its `Origin` header, or from its `Referer`
+/// one if it's missing.
+pub(super) fn request_origin(headers: &HeaderMap) -> Option<&str> {
+  headers
+    .get(header::ORIGIN)
+    .and_then(|value| value.to_str().ok())
+    .filter(|origin| *origin != "null")
+    .or_else(|| {
+      headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(origin_of)
+    })
+}
+
+/// The host the request was sent to, including its port. The `Forwarded` and
+/// `X-Forwarded-Host` headers are only read when `forwarded` is set since any
+/// client can send them when there is no proxy to overwrite them.
+pub(super) fn request_host(req: &HttpRequest, forwarded: bool) -> Option<&str> {
+  let headers = req.headers();
+
+  forwarded
+    .then(|| forwarded_header(headers, "host", "X-Forwarded-Host"))
+    .flatten()
+    .or_else(|| {
+      headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+    })
+    .or_else(|| req.uri().authority().map(|authority| authority.as_str()))
+}
+
+/// The scheme the request was sent with, read from the `Forwarded` and
+/// `X-Forwarded-Proto` headers when `forwarded` is set like in [request_host].
+pub(super) fn request_scheme(req: &HttpRequest, forwarded: bool) -> &str {
+  forwarded
+    .then(|| forwarded_header(req.headers(), "proto", "X-Forwarded-Proto"))
+    .flatten()
+    .or_else(|| req.uri().scheme_str())
+    .unwrap_or(match req.app_config().secure() {
+      true => "https",
+      false => "http"
+    })
+}
+
+/// Whether the origin has the scheme, the host and the port of the request.
+/// The default port of the scheme is ignored as browsers leave it out of the
+/// origins.
+pub(super) fn is_same_origin(origin: &str, scheme: &str, host: &str) -> bool {
+  let Some((origin_scheme, authority)) = origin.split_once("://") else {
+    return false;
+  };
+
+  origin_scheme.eq_ignore_ascii_case(scheme)
+    && without_default_port(scheme, authority)
+      .eq_ignore_ascii_case(without_default_port(scheme, host))
+}
+
+fn without_default_port<'a>(scheme: &str, authority: &'a str) -> &'a str {
+  let port = match scheme.to_ascii_lowercase().as_str() {
+    "http" => ":80",
+    "https" => ":443",
+    _ => return authority
+  };
+
+  authority.strip_suffix(port).unwrap_or(authority)
+}
+
+/// The parameter of the first element of the `Forwarded` header, or the first
+/// value of its `X-Forwarded-*` counterpart.
+fn forwarded_header<'a>(headers: &'a HeaderMap, param: &str, fallback: &str) -> Option<&'a str> {
+  let forwarded = headers
+    .get(header::FORWARDED)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.split(',').next())
+    .and_then(|element| {
+      element
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(param))
+    })
+    .map(|(_, value)| value.trim().trim_matches('"'));
+
+  forwarded.or_else(|| {
+    headers
+      .get(fallback)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.split(',').next())
+      .map(str::trim)
+  })
+}
+
+/// The `scheme://host[:port]` part of an URL
+fn origin_of(url: &str) -> Option<&str> {
+  let (_, rest) = url.split_once("://")?;
+  let end = url.len() - rest.len() + rest.find(['/', '?', '#']).unwrap_or(rest.len());
+
+  Some(&url[..end])
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::test::TestRequest;
+
+  use super::*;
+
+  #[test]
+  fn null_origin_falls_back_to_the_referer() {
+    let req = TestRequest::default()
+      .insert_header((header::ORIGIN, "null"))
+      .to_http_request();
+    assert_eq!(request_origin(req.headers()), None);
+
+    let req = TestRequest::default()
+      .insert_header((header::ORIGIN, "null"))
+      .insert_header((header::REFERER, "https://example.com/todos"))
+      .to_http_request();
+    assert_eq!(request_origin(req.headers()), Some("https://example.com"));
+  }
+
+  #[test]
+  fn referer_path_is_removed() {
+    for referer in [
+      "https://example.com/todos/3?page=2",
+      "https://example.com?page=2",
+      "https://example.com#top",
+      "https://example.com"
+    ] {
+      assert_eq!(origin_of(referer), Some("https://example.com"));
+    }
+
+    assert_eq!(origin_of("/todos"), None);
+  }
+
+  #[test]
+  fn port_mismatch() {
+    assert!(is_same_origin(
+      "http://example.com:8080",
+      "http",
+      "example.com:8080"
+    ));
+    assert!(!is_same_origin(
+      "http://example.com:8080",
+      "http",
+      "example.com:3000"
+    ));
+    assert!(!is_same_origin(
+      "http://example.com:8080",
+      "http",
+      "example.com"
+    ));
+  }
+
+  #[test]
+  fn default_ports_are_ignored() {
+    assert!(is_same_origin(
+      "https://example.com",
+      "https",
+      "example.com:443"
+    ));
+    assert!(is_same_origin(
+      "http://example.com:80",
+      "http",
+      "example.com"
+    ));
+    assert!(!is_same_origin(
+      "https://example.com:80",
+      "https",
+      "example.com"
+    ));
+  }
+
+  #[test]
+  fn scheme_mismatch() {
+    assert!(is_same_origin(
+      "HTTPS://Example.com",
+      "https",
+      "example.com"
+    ));
+    assert!(!is_same_origin(
+      "http://example.com",
+      "https",
+      "example.com"
+    ));
+    assert!(!is_same_origin(
+      "https://example.com",
+      "http",
+      "example.com"
+    ));
+    assert!(!is_same_origin("example.com", "http", "example.com"));
+  }
+
+  #[test]
+  fn forwarded_headers_are_only_read_when_trusted() {
+    let req = TestRequest::default()
+      .insert_header((header::HOST, "backend:8080"))
+      .insert_header(("X-Forwarded-Host", "example.com"))
+      .insert_header(("X-Forwarded-Proto", "https"))
+      .to_http_request();
+
+    assert_eq!(request_host(&req, false), Some("backend:8080"));
+    assert_eq!(request_scheme(&req, false), "http");
+    assert_eq!(request_host(&req, true), Some("example.com"));
+    assert_eq!(request_scheme(&req, true), "https");
+  }
+
+  #[test]
+  fn forwarded_lists_use_the_first_element() {
+    let req = TestRequest::default()
+      .insert_header(("X-Forwarded-Host", "example.com, proxy.internal"))
+      .insert_header(("X-Forwarded-Proto", "https, http"))
+      .to_http_request();
+
+    assert_eq!(request_host(&req, true), Some("example.com"));
+    assert_eq!(request_scheme(&req, true), "https");
+
+    let req = TestRequest::default()
+      .insert_header((
+        header::FORWARDED,
+        "for=1.2.3.4;proto=https;host=\"example.com\", for=10.0.0.1;host=proxy.internal"
+      ))
+      .insert_header(("X-Forwarded-Host", "ignored.com"))
+      .to_http_request();
+
+    assert_eq!(request_host(&req, true), Some("example.com"));
+    assert_eq!(request_scheme(&req, true), "https");
+  }
+}
@@ -0,0 +1,306 @@
+use std::future::{ready, Ready};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_web::{HttpMessage, HttpResponse};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The key the CSRF session is kept under in the user's session
+const SESSION_KEY: &str = "_lv_csrf";
+
+/// Enables the token mode of the CSRF protection once added to the app data:
+/// ```rs
+/// App::new().app_data(CsrfTokens::new(secret))
+/// ```
+///
+/// The value of the CSRF header must then be a token signed by the server and
+/// bound to the session cookie of the client, rather than any value. The
+/// tokens are issued by the [CsrfToken] extractor.
+///
+/// Since the token can't be forged without the secret, the `Sec-Fetch-Site`
+/// header is no longer required in this mode, which keeps the protection
+/// working behind proxies that strip it. Cross-site requests are still
+/// rejected when the header is present.
+#[derive(Clone)]
+pub struct CsrfTokens {
+  key: Vec<u8>,
+  ttl: Duration,
+  cookie_name: String,
+  secure: bool
+}
+
+impl CsrfTokens {
+  /// Uses the given secret to sign the tokens, it should be shared by all the
+  /// instances of the server.
+  pub fn new(secret: impl AsRef<[u8]>) -> Self {
+    Self {
+      key: secret.as_ref().to_vec(),
+      ttl: Duration::from_secs(24 * 60 * 60),
+      cookie_name: "lv-csrf-session".to_owned(),
+      secure: true
+    }
+  }
+
+  /// Uses a random secret, the tokens are then invalidated as soon as the
+  /// server restarts.
+  pub fn generate() -> Self {
+    Self::new(rand::random::<[u8; 32]>())
+  }
+
+  /// How long the tokens are valid for, 24 hours by default
+  pub fn ttl(mut self, ttl: Duration) -> Self {
+    self.ttl = ttl;
+    self
+  }
+
+  /// The name of the cookie that holds the session the tokens are bound to,
+  /// `lv-csrf-session` by default.
+  pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+    self.cookie_name = name.into();
+    self
+  }
+
+  /// Whether the session cookie is only sent over HTTPS, enabled by default
+  pub fn secure(mut self, secure: bool) -> Self {
+    self.secure = secure;
+    self
+  }
+
+  /// Issues a token for the session, in the `{expiry}.{signature}` format
+  pub fn issue(&self, session: &str) -> String {
+    let expires = now() + self.ttl.as_secs();
+    let signature = self.mac(session, expires).finalize().into_bytes();
+
+    format!("{expires}.{}", URL_SAFE_NO_PAD.encode(signature))
+  }
+
+  /// Verifies the token was issued for the session and hasn't expired
+  pub fn verify(&self, token: &str, session: &str) -> bool {
+    let Some((expires, signature)) = token.split_once('.') else {
+      return false;
+    };
+
+    let Ok(expires) = expires.parse::<u64>() else {
+      return false;
+    };
+
+    let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+      return false;
+    };
+
+    expires >= now() && self.mac(session, expires).verify_slice(&signature).is_ok()
+  }
+
+  /// Verifies the token of the request, from the CSRF header and the session
+  /// of the request.
+  pub(super) fn verify_request(&self, req: &actix_web::HttpRequest, header_name: &str) -> bool {
+    let token = req
+      .headers()
+      .get(header_name)
+      .and_then(|value| value.to_str().ok());
+
+    match (token, self.session(req)) {
+      (Some(token), Some(session)) => self.verify(token, &session),
+      _ => false
+    }
+  }
+
+  /// The session the tokens of the request are bound to, kept in the
+  /// [Session](crate::session::Session) if there is one or in its own cookie
+  /// otherwise.
+  pub(super) fn session(&self, req: &actix_web::HttpRequest) -> Option<String> {
+    if let Some(session) = req.extensions().get::<crate::session::Session>() {
+      return session.get(SESSION_KEY);
+    }
+
+    req
+      .headers()
+      .get_all(actix_web::http::header::COOKIE)
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(';'))
+      .filter_map(|cookie| actix_web::cookie::Cookie::parse(cookie.trim()).ok())
+      .find(|cookie| cookie.name() == self.cookie_name)
+      .map(|cookie| cookie.value().to_owned())
+  }
+
+  fn mac(&self, session: &str, expires: u64) -> Hmac<Sha256> {
+    let mut mac =
+      Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+    mac.update(format!("{session}.{expires}").as_bytes());
+
+    mac
+  }
+}
+
+/// A token issued for the session of the request, to render in the page so
+/// HTMX sends it along its requests:
+/// ```rs
+/// async fn endpoint(csrf: CsrfToken) -> HttpResponse {
+///   let page = html!(
+///     body hx-headers=(csrf.hx_headers()) {}
+///   );
+///
+///   csrf.set_cookie(page.into_response())
+/// }
+/// ```
+///
+/// A new session is started when the request doesn't have one, the response
+/// must then set the session cookie with [CsrfToken::set_cookie]. The cookie
+/// isn't needed when the [sessions](crate::session) are enabled since the
+/// CSRF session is then kept in the user's one.
+pub struct CsrfToken {
+  token: String,
+  header_name: String,
+  cookie: Option<actix_web::cookie::Cookie<'static>>
+}
+
+impl CsrfToken {
+  pub fn value(&self) -> &str {
+    &self.token
+  }
+
+  /// The value of a `hx-headers` attribute that sends the token in the CSRF
+  /// header.
+  pub fn hx_headers(&self) -> String {
+    serde_json::json!({ &self.header_name: self.token }).to_string()
+  }
+
+  /// Sets the session cookie the token is bound to on the response, if it
+  /// isn't kept in the user's [Session](crate::session::Session).
+  pub fn set_cookie(&self, mut res: HttpResponse) -> HttpResponse {
+    if let Some(cookie) = &self.cookie {
+      // the cookie only contains characters that are valid in a header
+      let _ = res.add_cookie(cookie);
+    }
+
+    res
+  }
+}
+
+impl actix_web::FromRequest for CsrfToken {
+  type Error = actix_web::Error;
+
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    let Some(tokens) = req.app_data::<CsrfTokens>() else {
+      return ready(Err(actix_web::error::ErrorInternalServerError(
+        "the CSRF tokens are missing from the app data"
+      )));
+    };
+
+    let header_name = super::config::config(req.app_data())
+      .get_header_name()
+      .to_owned();
+    let new_session = || URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+
+    if let Some(session) = req.extensions().get::<crate::session::Session>() {
+      let id = session.get(SESSION_KEY).unwrap_or_else(|| {
+        let id = new_session();
+        // a string always serializes
+        let _ = session.insert(SESSION_KEY, &id);
+
+        id
+      });
+
+      return ready(Ok(Self {
+        token: tokens.issue(&id),
+        header_name,
+        cookie: None
+      }));
+    }
+
+    let session = tokens.session(req).unwrap_or_else(new_session);
+    let cookie = actix_web::cookie::Cookie::build(tokens.cookie_name.clone(), session.clone())
+      .path("/")
+      .http_only(true)
+      .secure(tokens.secure)
+      .same_site(actix_web::cookie::SameSite::Strict)
+      .finish();
+
+    ready(Ok(Self {
+      token: tokens.issue(&session),
+      header_name,
+      cookie: Some(cookie)
+    }))
+  }
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn valid_token() {
+    let tokens = CsrfTokens::new("secret");
+    let token = tokens.issue("session");
+
+    assert!(tokens.verify(&token, "session"));
+  }
+
+  #[test]
+  fn tampered_signature() {
+    let tokens = CsrfTokens::new("secret");
+    let token = tokens.issue("session");
+    let (expires, signature) = token.split_once('.').unwrap();
+
+    let mut tampered = signature.to_owned().into_bytes();
+    tampered[0] = if tampered[0] == b'A' { b'B' } else { b'A' };
+    let tampered = format!("{expires}.{}", String::from_utf8(tampered).unwrap());
+    assert!(!tokens.verify(&tampered, "session"));
+
+    // the expiry is covered by the signature as well
+    let extended = format!("{}.{signature}", expires.parse::<u64>().unwrap() + 1);
+    assert!(!tokens.verify(&extended, "session"));
+
+    assert!(!CsrfTokens::new("other secret").verify(&token, "session"));
+  }
+
+  #[test]
+  fn token_of_another_session() {
+    let tokens = CsrfTokens::new("secret");
+
+    assert!(!tokens.verify(&tokens.issue("session"), "other session"));
+  }
+
+  #[test]
+  fn expired_token() {
+    let tokens = CsrfTokens::new("secret");
+    let expires = now() - 1;
+    let signature = tokens.mac("session", expires).finalize().into_bytes();
+    let token = format!("{expires}.{}", URL_SAFE_NO_PAD.encode(signature));
+
+    assert!(!tokens.verify(&token, "session"));
+  }
+
+  #[test]
+  fn malformed_tokens() {
+    let tokens = CsrfTokens::new("secret");
+    let expires = now() + 60;
+
+    for token in [
+      String::new(),
+      "token".to_owned(),
+      format!("{expires}"),
+      format!("{expires}."),
+      format!("soon.{}", URL_SAFE_NO_PAD.encode([0; 32])),
+      format!("{expires}.not base64!"),
+      format!("{expires}.{}", URL_SAFE_NO_PAD.encode([0; 31])),
+      format!("{expires}.{}", URL_SAFE_NO_PAD.encode([0; 33]))
+    ] {
+      assert!(
+        !tokens.verify(&token, "session"),
+        "`{token}` should be rejected"
+      );
+    }
+  }
+}
@@ -5,6 +5,11 @@ pub trait ExtMaudMarkup {
   /// in a new [maud::Markup] with `self` and `other` right after it.
   fn join(self, other: maud::Markup) -> Self;
 
+  /// Turns the current [maud::Markup] into an out-of-band swap targeting the
+  /// element with the given id, rendered inside a `tag` element. Refer to
+  /// [oob](crate::responses::oob).
+  fn oob(self, tag: &'static str, id: &str, swap: crate::htmx::Swap) -> Self;
+
   /// Turns the current [maud::Markup] into a HTTP response fit for the client.
   fn into_response(self) -> crate::responses::HttpResponse;
 
@@ -15,6 +20,27 @@ pub trait ExtMaudMarkup {
   fn into_response_with_event(
     self, event: impl crate::WithTrigger
   ) -> crate::responses::HttpResponse;
+
+  /// Same as [ExtMaudMarkup::into_response_with_event] except the event is
+  /// triggered once the new content has been swapped in.
+  fn into_response_with_event_after_swap(
+    self, event: impl crate::WithTrigger
+  ) -> crate::responses::HttpResponse;
+
+  /// Same as [ExtMaudMarkup::into_response_with_event] except the event is
+  /// triggered once the new content has been swapped in and settled.
+  fn into_response_with_event_after_settle(
+    self, event: impl crate::WithTrigger
+  ) -> crate::responses::HttpResponse;
+
+  /// Turns the current [maud::Markup] into a HTTP response fit for the client,
+  /// while also sending a trigger for each of the provided events.
+  ///
+  /// Accepts tuples of events, as well as vectors and arrays. Refer to
+  /// [WithTriggers](crate::WithTriggers).
+  fn into_response_with_events(
+    self, events: impl crate::WithTriggers
+  ) -> crate::responses::HttpResponse;
 }
 
 impl ExtMaudMarkup for maud::Markup {
@@ -23,6 +49,10 @@ impl ExtMaudMarkup for maud::Markup {
     self
   }
 
+  fn oob(self, tag: &'static str, id: &str, swap: crate::htmx::Swap) -> Self {
+    crate::responses::oob(self, tag, id, swap)
+  }
+
   fn into_response(self) -> crate::responses::HttpResponse {
     crate::responses::html(self)
   }
@@ -32,4 +62,22 @@ impl ExtMaudMarkup for maud::Markup {
   ) -> crate::responses::HttpResponse {
     event.trigger(self.into_response())
   }
+
+  fn into_response_with_event_after_swap(
+    self, event: impl crate::WithTrigger
+  ) -> crate::responses::HttpResponse {
+    crate::WithTrigger::trigger(event.after_swap(), self.into_response())
+  }
+
+  fn into_response_with_event_after_settle(
+    self, event: impl crate::WithTrigger
+  ) -> crate::responses::HttpResponse {
+    crate::WithTrigger::trigger(event.after_settle(), self.into_response())
+  }
+
+  fn into_response_with_events(
+    self, events: impl crate::WithTriggers
+  ) -> crate::responses::HttpResponse {
+    events.trigger(self.into_response())
+  }
 }
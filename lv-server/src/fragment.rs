@@ -1,4 +1,4 @@
-use crate::{WithRouter, WithTrigger};
+use crate::{EventListener, FragmentEvents, TriggerEvent, WithRouter, WithTrigger};
 
 /// Fragments are small parts of the [Views](crate::View) that need their
 /// own set of endpoints without polluting the view's router. They can be used as components with their own internal logic.
@@ -47,7 +47,8 @@ use crate::{WithRouter, WithTrigger};
 ///
 ///     data.add_todo(form.text);
 ///
-///     AddTodoForm::render().into_response_with_event(super::TodoListEvents::Reload)
+///     AddTodoForm::render()
+///       .into_response_with_event(super::TodoList::emit(super::TodoListEvents::Reload))
 ///   }
 /// }
 ///
@@ -55,11 +56,11 @@ use crate::{WithRouter, WithTrigger};
 ///   pub fn render() -> Markup {
 ///     html!(
 ///       form.fdn.row
-///         hx-post={(api::post_add_todo::url())}
-///         hx-target="this"
-///         hx-swap="outerHTML"
+///         hx-post=(api::post_add_todo::hx_post())
+///         hx-target=(Target::This)
+///         hx-swap=(Swap::OuterHtml)
 ///       {
-///         input name="text" placeholder="Todo's text" {}
+///         input name="text" placeholder="Todo's text";
 ///         input type="submit" value="Add";
 ///       }
 ///     )
@@ -74,6 +75,11 @@ where
 {
   const ID: &'static str;
 
+  /// The tag of the root element of the fragment, a `div` by default. Used by
+  /// [Fragment::root] and [Fragment::oob] so the root can be a `ul` or a `tr`
+  /// without being wrapped in a `div`.
+  const ROOT_TAG: &'static str = "div";
+
   fn url(path: &str) -> String {
     format!("/frg/{}/{}", Self::ID, path.trim_start_matches('/'))
   }
@@ -84,4 +90,177 @@ where
   ) -> &'a mut actix_web::web::ServiceConfig {
     cfg.route(&Self::url(path), route)
   }
+
+  /// The id of the root element of the fragment, which is its [Fragment::ID].
+  /// Every id and target of the fragment is derived from it.
+  fn root_id() -> String {
+    Self::ID.to_owned()
+  }
+
+  /// The id of a keyed element inside the fragment, `TodoList::child_id("item", 3)`
+  /// gives `TodoList-item-3`.
+  ///
+  /// As ids can't contain whitespace, the whitespace of the name and the key
+  /// is percent-encoded along with `%` so different keys never share an id:
+  /// `"a b"` gives `TodoList-item-a%20b`.
+  fn child_id(name: &str, key: impl std::fmt::Display) -> String {
+    let id = format!("{}-{name}-{key}", Self::root_id());
+
+    match id.contains(|c: char| c == '%' || c.is_whitespace()) {
+      true => id
+        .chars()
+        .map(|c| match c == '%' || c.is_whitespace() {
+          true => format!("%{:02X}", c as u32),
+          false => c.to_string()
+        })
+        .collect(),
+      false => id
+    }
+  }
+
+  /// Targets the root element of the fragment, so other elements can swap it
+  /// without relying on CSS selectors:
+  /// ```rs
+  /// button hx-get=(api::get_index::hx_get()) hx-target=(TodoList::target()) {"reload"}
+  /// ```
+  fn target() -> crate::htmx::Target {
+    crate::htmx::Target::id(&Self::root_id())
+  }
+
+  /// Targets a keyed element inside the fragment, refer to [Fragment::child_id].
+  /// The id is escaped in the selector, refer to [Target::id](crate::htmx::Target::id).
+  fn child_target(name: &str, key: impl std::fmt::Display) -> crate::htmx::Target {
+    crate::htmx::Target::id(&Self::child_id(name, key))
+  }
+
+  /// Renders the root element of the fragment with its [Fragment::root_id]
+  /// around the markup. Responses that replace the whole fragment are expected
+  /// to render it again, as it's the element [Fragment::target] and the
+  /// [Fragment::oob] swaps point to.
+  fn root(markup: maud::Markup) -> maud::Markup {
+    crate::htmx::element(Self::ROOT_TAG, [("id", Self::root_id())], markup)
+  }
+
+  /// Renders the markup as an out-of-band swap targeting the root element of
+  /// the fragment, so any response can update it:
+  /// ```rs
+  /// AddTodoForm::render()
+  ///   .join(TodoCounter::oob(TodoCounter::render_count(&data.todos()), Swap::InnerHtml))
+  ///   .into_response()
+  /// ```
+  ///
+  /// The markup is rendered inside a [Fragment::ROOT_TAG] element, with
+  /// [Swap::OuterHtml](crate::htmx::Swap::OuterHtml) that element becomes the
+  /// new root so the markup should be the content of the fragment, like the
+  /// one given to [Fragment::root].
+  fn oob(markup: maud::Markup, swap: crate::htmx::Swap) -> maud::Markup {
+    crate::responses::oob(markup, Self::ROOT_TAG, &Self::root_id(), swap)
+  }
+
+  /// Turns one of the fragment's events into a [TriggerEvent]:
+  /// ```rs
+  /// AddTodoForm::render().into_response_with_event(TodoList::emit(TodoListEvents::Reload))
+  /// ```
+  ///
+  /// Only the events declared for the fragment with
+  /// `events!(TodoListEvents for TodoList { .. })` are accepted, emitting the
+  /// events of another fragment or events without a fragment is a compile
+  /// error. These events are namespaced with the fragment's [Fragment::ID]
+  /// wherever they are used, so fragments don't react to each other's events:
+  /// ```rs
+  /// // sends `HX-Trigger: TodoList:TodoListEvents_Reload`
+  /// TodoList::emit(TodoListEvents::Reload)
+  /// ```
+  fn emit(event: Events) -> Result<TriggerEvent, serde_json::Error>
+  where
+    Self: Sized,
+    Events: FragmentEvents<Fragment = Self>
+  {
+    event.into_trigger()
+  }
+
+  /// The listening side of [Fragment::emit], a [Trigger](crate::htmx::Trigger)
+  /// for the event along with the filter and the modifiers of its
+  /// declaration:
+  /// ```rs
+  /// div hx-trigger=(TodoList::listen(TodoListEvents::RELOAD)) {}
+  /// ```
+  fn listen(listener: EventListener<Events>) -> crate::htmx::Trigger
+  where
+    Self: Sized,
+    Events: FragmentEvents<Fragment = Self>
+  {
+    let trigger = match listener.filter() {
+      Some(filter) => listener.listen().filter(filter),
+      None => listener.listen()
+    };
+
+    listener
+      .modifier()
+      .split_whitespace()
+      .fold(trigger, |trigger, modifier| {
+        trigger.modifier(modifier.to_owned())
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use maud::Render;
+
+  use super::*;
+
+  struct TodoList;
+
+  impl Fragment<(), ()> for TodoList {
+    const ID: &'static str = "TodoList";
+  }
+
+  struct TodoRows;
+
+  impl Fragment<(), ()> for TodoRows {
+    const ID: &'static str = "TodoRows";
+    const ROOT_TAG: &'static str = "tbody";
+  }
+
+  #[test]
+  fn ids() {
+    assert_eq!(TodoList::root_id(), "TodoList");
+    assert_eq!(TodoList::child_id("item", 3), "TodoList-item-3");
+  }
+
+  #[test]
+  fn targets() {
+    assert_eq!(TodoList::target().to_string(), "#TodoList");
+    assert_eq!(
+      TodoList::child_target("item", 3).to_string(),
+      "#TodoList-item-3"
+    );
+  }
+
+  #[test]
+  fn child_keys_are_escaped() {
+    assert_eq!(TodoList::child_id("item", "a b#1"), "TodoList-item-a%20b#1");
+    assert_eq!(TodoList::child_id("item", "a%20b"), "TodoList-item-a%2520b");
+    assert_eq!(
+      TodoList::child_target("item", "a b#1").to_string(),
+      r"#TodoList-item-a\%20b\#1"
+    );
+  }
+
+  #[test]
+  fn root() {
+    assert_eq!(
+      TodoList::root(maud::html!(p {"x"})).into_string(),
+      r#"<div id="TodoList"><p>x</p></div>"#
+    );
+    assert_eq!(
+      TodoRows::root(maud::html!(tr {})).into_string(),
+      r#"<tbody id="TodoRows"><tr></tr></tbody>"#
+    );
+    assert_eq!(
+      TodoList::child_target("item", "<a>").render().into_string(),
+      r"#TodoList-item-\&lt;a\&gt;"
+    );
+  }
 }
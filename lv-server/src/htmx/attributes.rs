@@ -0,0 +1,190 @@
+use super::{HxUrl, HxVerb, SwapSpec, Target, Trigger};
+
+/// A builder for the whole set of HTMX attributes of an element, seeded from
+/// the `hx_{verb}()` function of an endpoint:
+/// ```rs
+/// (api::delete_todo::hx_delete(index)
+///   .attributes()
+///   .target(Target::closest("li"))
+///   .swap(Swap::OuterHtml)
+///   .confirm("Delete this todo?")
+///   .element("button", "X"))
+/// ```
+///
+/// Since maud has no way to splice attributes into an element, the builder
+/// renders the element itself using [Attributes::element]. Each typed value
+/// can otherwise be used on its own as the value of its attribute:
+/// ```rs
+/// button hx-swap=(Swap::OuterHtml) hx-target=(Target::This) {}
+/// ```
+#[derive(Debug, Clone)]
+pub struct Attributes {
+  verb: &'static str,
+  url: String,
+  target: Option<Target>,
+  swap: Option<SwapSpec>,
+  triggers: Vec<Trigger>,
+  confirm: Option<String>,
+  include: Option<Target>,
+  vals: Option<String>,
+  indicator: Option<Target>,
+  extra: Vec<(&'static str, String)>
+}
+
+impl Attributes {
+  pub fn target(mut self, target: Target) -> Self {
+    self.target = Some(target);
+    self
+  }
+
+  pub fn swap(mut self, swap: impl Into<SwapSpec>) -> Self {
+    self.swap = Some(swap.into());
+    self
+  }
+
+  /// Adds a trigger to the element, it can be called multiple times to
+  /// respond to multiple triggers.
+  pub fn trigger(mut self, trigger: Trigger) -> Self {
+    self.triggers.push(trigger);
+    self
+  }
+
+  pub fn confirm(mut self, message: impl Into<String>) -> Self {
+    self.confirm = Some(message.into());
+    self
+  }
+
+  pub fn include(mut self, target: Target) -> Self {
+    self.include = Some(target);
+    self
+  }
+
+  /// Additional values to send with the request, as a JSON object:
+  /// ```rs
+  /// .vals(serde_json::json!({ "index": index }))
+  /// ```
+  pub fn vals(mut self, vals: serde_json::Value) -> Self {
+    self.vals = Some(vals.to_string());
+    self
+  }
+
+  pub fn indicator(mut self, target: Target) -> Self {
+    self.indicator = Some(target);
+    self
+  }
+
+  /// Any other attribute the element needs, like a `class` or an `id`
+  pub fn attribute(mut self, name: &'static str, value: impl Into<String>) -> Self {
+    self.extra.push((name, value.into()));
+    self
+  }
+
+  /// The list of attributes along with their values
+  pub fn pairs(&self) -> Vec<(&'static str, String)> {
+    let triggers = match self.triggers.is_empty() {
+      true => None,
+      false => Some(
+        self
+          .triggers
+          .iter()
+          .map(Trigger::to_string)
+          .collect::<Vec<_>>()
+          .join(", ")
+      )
+    };
+
+    [
+      Some((self.verb, self.url.clone())),
+      self.target.as_ref().map(|t| ("hx-target", t.to_string())),
+      self.swap.as_ref().map(|s| ("hx-swap", s.to_string())),
+      triggers.map(|t| ("hx-trigger", t)),
+      self.confirm.clone().map(|c| ("hx-confirm", c)),
+      self.include.as_ref().map(|i| ("hx-include", i.to_string())),
+      self.vals.clone().map(|v| ("hx-vals", v)),
+      self
+        .indicator
+        .as_ref()
+        .map(|i| ("hx-indicator", i.to_string()))
+    ]
+    .into_iter()
+    .flatten()
+    .chain(self.extra.iter().cloned())
+    .collect()
+  }
+
+  /// Renders an element with all of the attributes, and the content inside it.
+  pub fn element(&self, tag: &'static str, content: impl maud::Render) -> maud::Markup {
+    super::element(tag, self.pairs(), content)
+  }
+}
+
+impl<V: HxVerb> From<HxUrl<V>> for Attributes {
+  fn from(url: HxUrl<V>) -> Self {
+    Self {
+      verb: V::ATTRIBUTE,
+      url: url.url().to_owned(),
+      target: None,
+      swap: None,
+      triggers: Vec::new(),
+      confirm: None,
+      include: None,
+      vals: None,
+      indicator: None,
+      extra: Vec::new()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::htmx::{Delete, Swap};
+
+  fn url() -> HxUrl<Delete> {
+    HxUrl::new("/todos/1".to_owned())
+  }
+
+  #[test]
+  fn every_attribute() {
+    let attributes = url()
+      .attributes()
+      .target(Target::closest("li"))
+      .swap(Swap::OuterHtml)
+      .trigger(Trigger::click())
+      .trigger(Trigger::event("reload").from_body())
+      .confirm("Delete?")
+      .include(Target::This)
+      .vals(serde_json::json!({ "index": 1 }))
+      .indicator(Target::id("spinner"))
+      .attribute("class", "danger");
+
+    assert_eq!(
+      attributes.pairs(),
+      [
+        ("hx-delete", "/todos/1".to_owned()),
+        ("hx-target", "closest li".to_owned()),
+        ("hx-swap", "outerHTML".to_owned()),
+        ("hx-trigger", "click, reload from:body".to_owned()),
+        ("hx-confirm", "Delete?".to_owned()),
+        ("hx-include", "this".to_owned()),
+        ("hx-vals", r#"{"index":1}"#.to_owned()),
+        ("hx-indicator", "#spinner".to_owned()),
+        ("class", "danger".to_owned())
+      ]
+    );
+  }
+
+  #[test]
+  fn values_are_escaped() {
+    let markup = url()
+      .attributes()
+      .confirm("Delete \"<b>\"?")
+      .vals(serde_json::json!({ "text": "a\"b" }))
+      .element("button", "X");
+
+    assert_eq!(
+      markup.into_string(),
+      r#"<button hx-delete="/todos/1" hx-confirm="Delete &quot;&lt;b&gt;&quot;?" hx-vals="{&quot;text&quot;:&quot;a\&quot;b&quot;}">X</button>"#
+    );
+  }
+}
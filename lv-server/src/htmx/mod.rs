@@ -0,0 +1,93 @@
+//! Typed helpers to render the HTMX attributes of the elements, so the
+//! attributes and the endpoints they target can't disagree.
+
+mod verb;
+pub use verb::Delete;
+pub use verb::Get;
+pub use verb::HxUrl;
+pub use verb::HxVerb;
+pub use verb::Patch;
+pub use verb::Post;
+pub use verb::Put;
+
+mod swap;
+pub use swap::Scroll;
+pub use swap::Swap;
+pub use swap::SwapSpec;
+
+mod target;
+pub use target::Target;
+
+mod trigger;
+pub use trigger::Queue;
+pub use trigger::Trigger;
+
+mod attributes;
+pub use attributes::Attributes;
+
+mod request;
+pub use request::HxRequest;
+
+/// The elements that can't have any content, they are rendered without a
+/// closing tag.
+const VOID_TAGS: [&str; 13] = [
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+  "wbr"
+];
+
+/// Renders an element with the attributes and the content inside it, since
+/// maud can't splice attributes or tag names into an element. The content of
+/// the [void elements](VOID_TAGS) like `input` is ignored.
+pub(crate) fn element<'a>(
+  tag: &'static str, attributes: impl IntoIterator<Item = (&'a str, String)>,
+  content: impl maud::Render
+) -> maud::Markup {
+  use std::fmt::Write;
+
+  let mut buffer = format!("<{tag}");
+  for (name, value) in attributes {
+    buffer.push_str(&format!(" {name}=\""));
+    // writing to a String can't fail
+    let _ = write!(maud::Escaper::new(&mut buffer), "{value}");
+    buffer.push('"');
+  }
+
+  buffer.push('>');
+  if !VOID_TAGS.contains(&tag) {
+    content.render_to(&mut buffer);
+    buffer.push_str(&format!("</{tag}>"));
+  }
+
+  maud::PreEscaped(buffer)
+}
+
+/// Formats a duration the way HTMX expects it in its attributes, like `500ms`
+fn duration(duration: std::time::Duration) -> String {
+  format!("{}ms", duration.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn element_escapes_the_attributes() {
+    let markup = element(
+      "button",
+      [("hx-confirm", "Delete \"a\" & <b>?".to_owned())],
+      "X"
+    );
+
+    assert_eq!(
+      markup.into_string(),
+      r#"<button hx-confirm="Delete &quot;a&quot; &amp; &lt;b&gt;?">X</button>"#
+    );
+  }
+
+  #[test]
+  fn void_elements_are_not_closed() {
+    let markup = element("input", [("name", "text".to_owned())], "ignored");
+
+    assert_eq!(markup.into_string(), r#"<input name="text">"#);
+  }
+}
@@ -0,0 +1,159 @@
+use std::future::{ready, Ready};
+
+use actix_web::http::header::HeaderMap;
+
+/// An extractor for the headers HTMX sends with its requests, so endpoints can
+/// tell how they were invoked:
+/// ```rs
+/// impl api::get_index::Router {
+///   async fn endpoint(hx: HxRequest, data: ApiData) -> HttpResponse {
+///     match hx.is_partial() {
+///       true => ViewHome::render(data).into_response(),
+///       false => page(ViewHome::render(data)).into_response()
+///     }
+///   }
+/// }
+/// ```
+///
+/// The extraction never fails, the fields are simply empty for the requests
+/// that weren't sent by HTMX.
+#[derive(Debug, Clone, Default)]
+pub struct HxRequest {
+  /// `HX-Request`, whether the request was sent by HTMX
+  pub is_htmx: bool,
+
+  /// `HX-Boosted`, whether the request comes from an element using `hx-boost`
+  pub boosted: bool,
+
+  /// `HX-History-Restore-Request`, whether the request is for a history
+  /// restoration after a miss in the local history cache
+  pub history_restore: bool,
+
+  /// `HX-Current-URL`, the current URL of the browser
+  pub current_url: Option<String>,
+
+  /// `HX-Target`, the id of the target element if it has one
+  pub target: Option<String>,
+
+  /// `HX-Trigger`, the id of the triggered element if it has one
+  pub trigger: Option<String>,
+
+  /// `HX-Trigger-Name`, the name of the triggered element if it has one
+  pub trigger_name: Option<String>,
+
+  /// `HX-Prompt`, the user's response to a `hx-prompt`
+  pub prompt: Option<String>
+}
+
+impl HxRequest {
+  pub fn from_headers(headers: &HeaderMap) -> Self {
+    let header = |name: &str| {
+      headers
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    };
+    let flag = |name: &str| header(name).is_some_and(|value| value == "true");
+
+    Self {
+      is_htmx: flag("HX-Request"),
+      boosted: flag("HX-Boosted"),
+      history_restore: flag("HX-History-Restore-Request"),
+      current_url: header("HX-Current-URL"),
+      target: header("HX-Target"),
+      trigger: header("HX-Trigger"),
+      trigger_name: header("HX-Trigger-Name"),
+      prompt: header("HX-Prompt")
+    }
+  }
+
+  /// Whether the request expects a fragment of the page rather than a whole
+  /// page: it was sent by HTMX but isn't a boosted navigation nor a history
+  /// restoration.
+  pub fn is_partial(&self) -> bool {
+    self.is_htmx && !self.boosted && !self.history_restore
+  }
+}
+
+impl actix_web::FromRequest for HxRequest {
+  type Error = std::convert::Infallible;
+
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    ready(Ok(Self::from_headers(req.headers())))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::test::TestRequest;
+  use actix_web::FromRequest;
+
+  use super::*;
+
+  async fn extract(req: TestRequest) -> HxRequest {
+    let (req, mut payload) = req.to_http_parts();
+
+    HxRequest::from_request(&req, &mut payload).await.unwrap()
+  }
+
+  #[actix_web::test]
+  async fn htmx_requests_are_partial() {
+    let hx = extract(TestRequest::default().insert_header(("HX-Request", "true"))).await;
+
+    assert!(hx.is_htmx);
+    assert!(hx.is_partial());
+  }
+
+  #[actix_web::test]
+  async fn boosted_requests_are_not_partial() {
+    let req = TestRequest::default()
+      .insert_header(("HX-Request", "true"))
+      .insert_header(("HX-Boosted", "true"));
+    let hx = extract(req).await;
+
+    assert!(hx.boosted);
+    assert!(!hx.is_partial());
+
+    let req = TestRequest::default()
+      .insert_header(("HX-Request", "true"))
+      .insert_header(("HX-History-Restore-Request", "true"));
+    assert!(!extract(req).await.is_partial());
+  }
+
+  #[actix_web::test]
+  async fn requests_without_htmx_headers() {
+    let hx = extract(TestRequest::default()).await;
+
+    assert!(!hx.is_htmx);
+    assert!(!hx.is_partial());
+    assert_eq!(hx.target, None);
+    assert_eq!(hx.trigger, None);
+    assert_eq!(hx.current_url, None);
+
+    // only `true` counts as set
+    let req = TestRequest::default().insert_header(("HX-Request", "false"));
+    assert!(!extract(req).await.is_htmx);
+  }
+
+  #[actix_web::test]
+  async fn element_headers() {
+    let req = TestRequest::default()
+      .insert_header(("HX-Request", "true"))
+      .insert_header(("HX-Target", "TodoList"))
+      .insert_header(("HX-Trigger", "TodoList-item-3"))
+      .insert_header(("HX-Trigger-Name", "text"))
+      .insert_header(("HX-Current-URL", "http://localhost:3000/?page=2"))
+      .insert_header(("HX-Prompt", "yes"));
+    let hx = extract(req).await;
+
+    assert_eq!(hx.target.as_deref(), Some("TodoList"));
+    assert_eq!(hx.trigger.as_deref(), Some("TodoList-item-3"));
+    assert_eq!(hx.trigger_name.as_deref(), Some("text"));
+    assert_eq!(
+      hx.current_url.as_deref(),
+      Some("http://localhost:3000/?page=2")
+    );
+    assert_eq!(hx.prompt.as_deref(), Some("yes"));
+  }
+}
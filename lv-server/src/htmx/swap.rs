@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+/// The strategies for the `hx-swap` attribute, describing how the response is
+/// swapped in relative to the target element:
+/// ```rs
+/// form hx-swap=(Swap::OuterHtml) {}
+/// ```
+///
+/// Modifiers can be added to any strategy, turning it into a [SwapSpec]:
+/// ```rs
+/// Swap::InnerHtml.settle(Duration::from_millis(100)).scroll(Scroll::Top)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Swap {
+  InnerHtml,
+  OuterHtml,
+  TextContent,
+  BeforeBegin,
+  AfterBegin,
+  BeforeEnd,
+  AfterEnd,
+  Delete,
+  None
+}
+
+impl Swap {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Swap::InnerHtml => "innerHTML",
+      Swap::OuterHtml => "outerHTML",
+      Swap::TextContent => "textContent",
+      Swap::BeforeBegin => "beforebegin",
+      Swap::AfterBegin => "afterbegin",
+      Swap::BeforeEnd => "beforeend",
+      Swap::AfterEnd => "afterend",
+      Swap::Delete => "delete",
+      Swap::None => "none"
+    }
+  }
+
+  pub fn transition(self) -> SwapSpec {
+    SwapSpec::from(self).transition()
+  }
+
+  pub fn swap_delay(self, delay: Duration) -> SwapSpec {
+    SwapSpec::from(self).swap_delay(delay)
+  }
+
+  pub fn settle(self, delay: Duration) -> SwapSpec {
+    SwapSpec::from(self).settle(delay)
+  }
+
+  pub fn scroll(self, scroll: Scroll) -> SwapSpec {
+    SwapSpec::from(self).scroll(scroll)
+  }
+
+  pub fn show(self, scroll: Scroll) -> SwapSpec {
+    SwapSpec::from(self).show(scroll)
+  }
+
+  pub fn focus_scroll(self, focus_scroll: bool) -> SwapSpec {
+    SwapSpec::from(self).focus_scroll(focus_scroll)
+  }
+
+  pub fn ignore_title(self) -> SwapSpec {
+    SwapSpec::from(self).ignore_title()
+  }
+}
+
+impl maud::Render for Swap {
+  fn render_to(&self, buffer: &mut String) {
+    buffer.push_str(self.as_str());
+  }
+}
+
+/// Where the `scroll:` and `show:` modifiers of a [SwapSpec] move the
+/// viewport to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scroll {
+  Top,
+  Bottom,
+
+  /// The top of the element matching the CSS selector
+  SelectorTop(String),
+
+  /// The bottom of the element matching the CSS selector
+  SelectorBottom(String)
+}
+
+impl std::fmt::Display for Scroll {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Scroll::Top => write!(f, "top"),
+      Scroll::Bottom => write!(f, "bottom"),
+      Scroll::SelectorTop(selector) => write!(f, "{selector}:top"),
+      Scroll::SelectorBottom(selector) => write!(f, "{selector}:bottom")
+    }
+  }
+}
+
+/// A [Swap] strategy along with its modifiers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapSpec {
+  swap: Swap,
+  modifiers: Vec<String>
+}
+
+impl SwapSpec {
+  pub fn transition(self) -> Self {
+    self.modifier("transition:true".to_owned())
+  }
+
+  pub fn swap_delay(self, delay: Duration) -> Self {
+    self.modifier(format!("swap:{}", super::duration(delay)))
+  }
+
+  pub fn settle(self, delay: Duration) -> Self {
+    self.modifier(format!("settle:{}", super::duration(delay)))
+  }
+
+  pub fn scroll(self, scroll: Scroll) -> Self {
+    self.modifier(format!("scroll:{scroll}"))
+  }
+
+  pub fn show(self, scroll: Scroll) -> Self {
+    self.modifier(format!("show:{scroll}"))
+  }
+
+  pub fn focus_scroll(self, focus_scroll: bool) -> Self {
+    self.modifier(format!("focus-scroll:{focus_scroll}"))
+  }
+
+  pub fn ignore_title(self) -> Self {
+    self.modifier("ignoreTitle:true".to_owned())
+  }
+
+  fn modifier(mut self, modifier: String) -> Self {
+    self.modifiers.push(modifier);
+    self
+  }
+}
+
+impl From<Swap> for SwapSpec {
+  fn from(swap: Swap) -> Self {
+    Self {
+      swap,
+      modifiers: Vec::new()
+    }
+  }
+}
+
+impl std::fmt::Display for SwapSpec {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.swap.as_str())?;
+
+    for modifier in &self.modifiers {
+      write!(f, " {modifier}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl maud::Render for SwapSpec {
+  fn render_to(&self, buffer: &mut String) {
+    self.to_string().render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use maud::Render;
+
+  use super::*;
+
+  #[test]
+  fn strategies() {
+    assert_eq!(Swap::OuterHtml.render().into_string(), "outerHTML");
+    assert_eq!(Swap::BeforeEnd.render().into_string(), "beforeend");
+    assert_eq!(SwapSpec::from(Swap::None).to_string(), "none");
+  }
+
+  #[test]
+  fn modifiers_are_rendered_in_order() {
+    let spec = Swap::InnerHtml
+      .transition()
+      .swap_delay(Duration::from_millis(50))
+      .settle(Duration::from_secs(1))
+      .scroll(Scroll::Top)
+      .show(Scroll::SelectorBottom("#list".to_owned()))
+      .focus_scroll(false)
+      .ignore_title();
+
+    assert_eq!(
+      spec.to_string(),
+      "innerHTML transition:true swap:50ms settle:1000ms scroll:top show:#list:bottom focus-scroll:false ignoreTitle:true"
+    );
+  }
+
+  #[test]
+  fn spec_is_escaped() {
+    let spec = Swap::OuterHtml.show(Scroll::SelectorTop("a[href=\"/\"]".to_owned()));
+
+    assert_eq!(
+      spec.render().into_string(),
+      "outerHTML show:a[href=&quot;/&quot;]:top"
+    );
+  }
+}
@@ -0,0 +1,176 @@
+/// The elements the `hx-target`, `hx-include` and `hx-indicator` attributes
+/// (as well as the `from:` modifier of the triggers) can point at:
+/// ```rs
+/// button hx-target=(Target::closest("li")) {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+  /// The element the attribute is declared on
+  This,
+
+  /// The closest ancestor matching the CSS selector, or the element itself
+  Closest(String),
+
+  /// The first child matching the CSS selector
+  Find(String),
+
+  /// The next sibling, or the next element matching the CSS selector
+  Next(Option<String>),
+
+  /// The previous sibling, or the previous element matching the CSS selector
+  Previous(Option<String>),
+
+  Body,
+  Document,
+  Window,
+
+  /// Any CSS selector, like `#lv-alert`
+  Selector(String)
+}
+
+impl Target {
+  pub fn closest(selector: impl Into<String>) -> Self {
+    Self::Closest(selector.into())
+  }
+
+  pub fn find(selector: impl Into<String>) -> Self {
+    Self::Find(selector.into())
+  }
+
+  pub fn next() -> Self {
+    Self::Next(None)
+  }
+
+  pub fn next_matching(selector: impl Into<String>) -> Self {
+    Self::Next(Some(selector.into()))
+  }
+
+  pub fn previous() -> Self {
+    Self::Previous(None)
+  }
+
+  pub fn previous_matching(selector: impl Into<String>) -> Self {
+    Self::Previous(Some(selector.into()))
+  }
+
+  pub fn selector(selector: impl Into<String>) -> Self {
+    Self::Selector(selector.into())
+  }
+
+  /// The element with the given id, which is escaped so ids with spaces or
+  /// CSS characters like `#` and `.` are still read as a single id.
+  pub fn id(id: &str) -> Self {
+    Self::Selector(format!("#{}", escape_identifier(id)))
+  }
+
+  /// Whether the CSS selector spans multiple words, in which case HTMX needs
+  /// it wrapped in parentheses in the trigger modifiers.
+  pub(crate) fn has_multi_word_selector(&self) -> bool {
+    match self {
+      Target::Closest(selector)
+      | Target::Find(selector)
+      | Target::Next(Some(selector))
+      | Target::Previous(Some(selector))
+      | Target::Selector(selector) => selector.trim().contains(char::is_whitespace),
+      _ => false
+    }
+  }
+}
+
+/// Escapes the identifier the way `CSS.escape()` does so it can be used in a
+/// selector as is.
+fn escape_identifier(identifier: &str) -> String {
+  let mut escaped = String::with_capacity(identifier.len());
+  let starts_with_dash = identifier.starts_with('-');
+
+  for (i, c) in identifier.chars().enumerate() {
+    match c {
+      '\0' => escaped.push('\u{FFFD}'),
+      '\u{1}'..='\u{1F}' | '\u{7F}' => escaped.push_str(&format!("\\{:x} ", c as u32)),
+      '0'..='9' if i == 0 || (i == 1 && starts_with_dash) => {
+        escaped.push_str(&format!("\\{:x} ", c as u32))
+      }
+      '-' if identifier.len() == 1 => escaped.push_str("\\-"),
+      c if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() => escaped.push(c),
+      c => {
+        escaped.push('\\');
+        escaped.push(c);
+      }
+    }
+  }
+
+  escaped
+}
+
+impl std::fmt::Display for Target {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Target::This => write!(f, "this"),
+      Target::Closest(selector) => write!(f, "closest {selector}"),
+      Target::Find(selector) => write!(f, "find {selector}"),
+      Target::Next(None) => write!(f, "next"),
+      Target::Next(Some(selector)) => write!(f, "next {selector}"),
+      Target::Previous(None) => write!(f, "previous"),
+      Target::Previous(Some(selector)) => write!(f, "previous {selector}"),
+      Target::Body => write!(f, "body"),
+      Target::Document => write!(f, "document"),
+      Target::Window => write!(f, "window"),
+      Target::Selector(selector) => write!(f, "{selector}")
+    }
+  }
+}
+
+impl maud::Render for Target {
+  fn render_to(&self, buffer: &mut String) {
+    self.to_string().render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use maud::Render;
+
+  use super::*;
+
+  #[test]
+  fn relative_targets() {
+    assert_eq!(Target::This.to_string(), "this");
+    assert_eq!(Target::closest("li").to_string(), "closest li");
+    assert_eq!(Target::find(".item").to_string(), "find .item");
+    assert_eq!(Target::next().to_string(), "next");
+    assert_eq!(Target::next_matching("li").to_string(), "next li");
+    assert_eq!(Target::previous().to_string(), "previous");
+    assert_eq!(Target::previous_matching("li").to_string(), "previous li");
+  }
+
+  #[test]
+  fn selectors() {
+    assert_eq!(Target::Body.to_string(), "body");
+    assert_eq!(Target::id("lv-alert").to_string(), "#lv-alert");
+    assert_eq!(
+      Target::selector("ul > li").render().into_string(),
+      "ul &gt; li"
+    );
+  }
+
+  #[test]
+  fn ids_are_escaped() {
+    assert_eq!(
+      Target::id("TodoList-item-3").to_string(),
+      "#TodoList-item-3"
+    );
+    assert_eq!(Target::id("a b#c.d").to_string(), r"#a\ b\#c\.d");
+    assert_eq!(Target::id("3d").to_string(), r"#\33 d");
+    assert_eq!(Target::id("-2").to_string(), r"#-\32 ");
+    assert_eq!(Target::id("-").to_string(), r"#\-");
+    assert_eq!(Target::id("été\n").to_string(), r"#été\a ");
+  }
+
+  #[test]
+  fn multi_word_selectors() {
+    assert!(!Target::closest("li").has_multi_word_selector());
+    assert!(!Target::Body.has_multi_word_selector());
+    assert!(Target::closest("div .item").has_multi_word_selector());
+    assert!(Target::selector("ul > li").has_multi_word_selector());
+  }
+}
@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use super::Target;
+
+/// A typed `hx-trigger` specification, an event along with its filter and
+/// modifiers:
+/// ```rs
+/// input hx-trigger=(Trigger::keyup().changed().delay(Duration::from_millis(500))) {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigger {
+  event: String,
+  filter: Option<String>,
+  modifiers: Vec<String>
+}
+
+/// The strategies of the `queue:` modifier of a [Trigger]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Queue {
+  First,
+  Last,
+  All,
+  None
+}
+
+impl Trigger {
+  /// A trigger for any event, standard or custom
+  pub fn event(name: impl Into<String>) -> Self {
+    Self {
+      event: name.into(),
+      filter: None,
+      modifiers: Vec::new()
+    }
+  }
+
+  pub fn click() -> Self {
+    Self::event("click")
+  }
+
+  pub fn change() -> Self {
+    Self::event("change")
+  }
+
+  pub fn submit() -> Self {
+    Self::event("submit")
+  }
+
+  pub fn input() -> Self {
+    Self::event("input")
+  }
+
+  pub fn keyup() -> Self {
+    Self::event("keyup")
+  }
+
+  /// Triggered once the element is loaded
+  pub fn load() -> Self {
+    Self::event("load")
+  }
+
+  /// Triggered once the element is scrolled into the viewport
+  pub fn revealed() -> Self {
+    Self::event("revealed")
+  }
+
+  /// Triggered when the element first intersects the viewport
+  pub fn intersect() -> Self {
+    Self::event("intersect")
+  }
+
+  /// Polls at the given interval
+  pub fn every(interval: Duration) -> Self {
+    Self::event(format!("every {}", super::duration(interval)))
+  }
+
+  /// Listens for the event on the Server-Sent Events connection of the
+  /// closest `sse-connect` element rather than on the DOM, refer to the
+  /// [sse](crate::sse) module.
+  pub fn sse(mut self) -> Self {
+    self.event = format!("sse:{}", self.event);
+    self
+  }
+
+  /// A javascript expression the event must satisfy, like `ctrlKey`
+  pub fn filter(mut self, filter: impl Into<String>) -> Self {
+    self.filter = Some(filter.into());
+    self
+  }
+
+  pub fn once(self) -> Self {
+    self.modifier("once".to_owned())
+  }
+
+  /// Only issues a request if the value of the element has changed
+  pub fn changed(self) -> Self {
+    self.modifier("changed".to_owned())
+  }
+
+  pub fn delay(self, delay: Duration) -> Self {
+    self.modifier(format!("delay:{}", super::duration(delay)))
+  }
+
+  pub fn throttle(self, throttle: Duration) -> Self {
+    self.modifier(format!("throttle:{}", super::duration(throttle)))
+  }
+
+  /// Listens for the event on another element
+  pub fn from(self, target: Target) -> Self {
+    let target = match target.has_multi_word_selector() {
+      true => format!("({target})"),
+      false => target.to_string()
+    };
+
+    self.modifier(format!("from:{target}"))
+  }
+
+  /// Listens for the event on the closest ancestor matching the CSS selector
+  pub fn from_closest(self, selector: &str) -> Self {
+    self.from(Target::closest(selector))
+  }
+
+  /// Listens for the event on the body, where the events sent by the server
+  /// are triggered when the element that sent the request was removed.
+  pub fn from_body(self) -> Self {
+    self.from(Target::Body)
+  }
+
+  pub fn from_document(self) -> Self {
+    self.from(Target::Document)
+  }
+
+  pub fn from_window(self) -> Self {
+    self.from(Target::Window)
+  }
+
+  /// Only triggers when the event's target matches the CSS selector
+  pub fn target(self, selector: &str) -> Self {
+    match selector.contains(char::is_whitespace) {
+      true => self.modifier(format!("target:({selector})")),
+      false => self.modifier(format!("target:{selector}"))
+    }
+  }
+
+  /// Stops the event from triggering requests on the parent elements
+  pub fn consume(self) -> Self {
+    self.modifier("consume".to_owned())
+  }
+
+  pub fn queue(self, queue: Queue) -> Self {
+    let queue = match queue {
+      Queue::First => "first",
+      Queue::Last => "last",
+      Queue::All => "all",
+      Queue::None => "none"
+    };
+
+    self.modifier(format!("queue:{queue}"))
+  }
+
+  pub(crate) fn modifier(mut self, modifier: String) -> Self {
+    self.modifiers.push(modifier);
+    self
+  }
+}
+
+impl std::fmt::Display for Trigger {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.event)?;
+
+    if let Some(filter) = &self.filter {
+      write!(f, "[{filter}]")?;
+    }
+
+    for modifier in &self.modifiers {
+      write!(f, " {modifier}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl maud::Render for Trigger {
+  fn render_to(&self, buffer: &mut String) {
+    self.to_string().render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn events() {
+    assert_eq!(Trigger::click().to_string(), "click");
+    assert_eq!(
+      Trigger::every(Duration::from_secs(2)).to_string(),
+      "every 2000ms"
+    );
+    assert_eq!(Trigger::event("reload").sse().to_string(), "sse:reload");
+  }
+
+  #[test]
+  fn filter_comes_before_the_modifiers() {
+    let trigger = Trigger::keyup()
+      .changed()
+      .filter("key=='Enter'")
+      .delay(Duration::from_millis(500));
+
+    assert_eq!(
+      trigger.to_string(),
+      "keyup[key=='Enter'] changed delay:500ms"
+    );
+  }
+
+  #[test]
+  fn modifiers_are_rendered_in_order() {
+    let trigger = Trigger::click()
+      .once()
+      .throttle(Duration::from_secs(1))
+      .from_body()
+      .target(".item")
+      .consume()
+      .queue(Queue::Last);
+
+    assert_eq!(
+      trigger.to_string(),
+      "click once throttle:1000ms from:body target:.item consume queue:last"
+    );
+  }
+
+  #[test]
+  fn multi_word_selectors_are_wrapped() {
+    assert_eq!(
+      Trigger::click().from_closest("li").to_string(),
+      "click from:closest li"
+    );
+    assert_eq!(
+      Trigger::click().from_closest("div .item").to_string(),
+      "click from:(closest div .item)"
+    );
+    assert_eq!(
+      Trigger::click().target("ul > li").to_string(),
+      "click target:(ul > li)"
+    );
+  }
+}
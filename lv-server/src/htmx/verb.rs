@@ -0,0 +1,117 @@
+use std::marker::PhantomData;
+
+/// The HTTP verbs HTMX can send requests with, each one having its own
+/// attribute.
+pub trait HxVerb {
+  /// The HTMX attribute that issues a request with this verb, like `hx-get`
+  const ATTRIBUTE: &'static str;
+}
+
+pub struct Get;
+impl HxVerb for Get {
+  const ATTRIBUTE: &'static str = "hx-get";
+}
+
+pub struct Post;
+impl HxVerb for Post {
+  const ATTRIBUTE: &'static str = "hx-post";
+}
+
+pub struct Put;
+impl HxVerb for Put {
+  const ATTRIBUTE: &'static str = "hx-put";
+}
+
+pub struct Patch;
+impl HxVerb for Patch {
+  const ATTRIBUTE: &'static str = "hx-patch";
+}
+
+pub struct Delete;
+impl HxVerb for Delete {
+  const ATTRIBUTE: &'static str = "hx-delete";
+}
+
+/// The URL of an endpoint along with the verb it expects, returned by the
+/// `hx_{verb}()` functions the [endpoints!](crate::endpoints) macro generates
+/// for each verb an endpoint accepts.
+///
+/// It renders as the URL so it can be used as the value of the attribute of
+/// its verb:
+/// ```rs
+/// button hx-delete=(api::delete_todo::hx_delete(index)) {"X"}
+/// ```
+///
+/// Since the functions only exist for the verbs the endpoint was declared
+/// with, calling `api::delete_todo::hx_get()` is a compile error. The element
+/// can also be rendered with the attribute of the verb by [HxUrl::element], so
+/// the URL can't end up in the attribute of another verb.
+pub struct HxUrl<V: HxVerb> {
+  url: String,
+  verb: PhantomData<V>
+}
+
+impl<V: HxVerb> HxUrl<V> {
+  pub fn new(url: String) -> Self {
+    Self {
+      url,
+      verb: PhantomData
+    }
+  }
+
+  /// The raw URL, for the places that aren't HTMX attributes like a `href`
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  /// The HTMX attribute this URL is expected to be used with, like `hx-get`
+  pub fn attribute(&self) -> &'static str {
+    V::ATTRIBUTE
+  }
+
+  /// Starts a builder for all of the HTMX attributes of the element that will
+  /// send the request.
+  pub fn attributes(self) -> super::Attributes {
+    super::Attributes::from(self)
+  }
+
+  /// Renders an element with the `hx-{verb}` attribute of the URL, and the
+  /// content inside it. Use [HxUrl::attributes] for elements that need more
+  /// attributes.
+  pub fn element(self, tag: &'static str, content: impl maud::Render) -> maud::Markup {
+    self.attributes().element(tag, content)
+  }
+}
+
+impl<V: HxVerb> maud::Render for HxUrl<V> {
+  fn render_to(&self, buffer: &mut String) {
+    self.url.render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn renders_as_the_url() {
+    let url = HxUrl::<Delete>::new("/todos/1?a=b&c=d".to_owned());
+
+    assert_eq!(
+      maud::html!(button hx-delete=(url) {"X"}).into_string(),
+      r#"<button hx-delete="/todos/1?a=b&amp;c=d">X</button>"#
+    );
+  }
+
+  #[test]
+  fn element_uses_the_attribute_of_the_verb() {
+    let url = HxUrl::<Post>::new("/todos".to_owned());
+
+    assert_eq!(
+      url
+        .element("form", maud::html!(input name="text";))
+        .into_string(),
+      r#"<form hx-post="/todos"><input name="text"></form>"#
+    );
+  }
+}
@@ -11,12 +11,24 @@ pub use with_scope::WithScope;
 
 mod with_trigger;
 pub use with_trigger::WithTrigger;
+pub use with_trigger::WithTriggers;
+pub use with_trigger::{EventListener, FragmentEvents, TriggerEvent, TriggerPhase};
 
 mod ext_maud;
 pub use ext_maud::ExtMaudMarkup;
 
 pub mod csrf;
 
+pub mod url;
+
+pub mod htmx;
+
+pub mod sse;
+
+pub mod ws;
+
+pub mod session;
+
 mod view;
 pub use view::View;
 
@@ -35,4 +47,6 @@ pub use lv_server_endpoints_proc_macro::events;
 pub mod deps {
   pub use actix_web;
   pub use maud;
+  pub use serde;
+  pub use serde_json;
 }
@@ -8,7 +8,7 @@ use actix_web::error::{ErrorConflict, ErrorNotFound};
 /// Once wrapped in a [Need], this offers a convenient wrapper around
 /// Actix' [actix_web::FromRequest] trait.
 ///
-/// ```
+/// ```rs
 /// #[async_trait::async_trait]
 /// impl lv_server::PathExtractor for Todo {
 ///   type Params = String;
@@ -118,25 +118,15 @@ where
   fn params(
     req: &actix_web::HttpRequest, payload: &mut actix_web::dev::Payload
   ) -> Option<Self::Params> {
-    let Some(p1) = PE1::params(req, payload) else {
-      return None;
-    };
-
-    let Some(p2) = PE2::params(req, payload) else {
-      return None;
-    };
+    let p1 = PE1::params(req, payload)?;
+    let p2 = PE2::params(req, payload)?;
 
     Some((p1, p2))
   }
 
   async fn from_params(params: Self::Params) -> Option<Self> {
-    let Some(p1) = PE1::from_params(params.0).await else {
-      return None;
-    };
-
-    let Some(p2) = PE2::from_params(params.1).await else {
-      return None;
-    };
+    let p1 = PE1::from_params(params.0).await?;
+    let p2 = PE2::from_params(params.1).await?;
 
     Some((p1, p2))
   }
@@ -159,33 +149,17 @@ where
   fn params(
     req: &actix_web::HttpRequest, payload: &mut actix_web::dev::Payload
   ) -> Option<Self::Params> {
-    let Some(p1) = PE1::params(req, payload) else {
-      return None;
-    };
-
-    let Some(p2) = PE2::params(req, payload) else {
-      return None;
-    };
-
-    let Some(p3) = PE3::params(req, payload) else {
-      return None;
-    };
+    let p1 = PE1::params(req, payload)?;
+    let p2 = PE2::params(req, payload)?;
+    let p3 = PE3::params(req, payload)?;
 
     Some((p1, p2, p3))
   }
 
   async fn from_params(params: Self::Params) -> Option<Self> {
-    let Some(p1) = PE1::from_params(params.0).await else {
-      return None;
-    };
-
-    let Some(p2) = PE2::from_params(params.1).await else {
-      return None;
-    };
-
-    let Some(p3) = PE3::from_params(params.2).await else {
-      return None;
-    };
+    let p1 = PE1::from_params(params.0).await?;
+    let p2 = PE2::from_params(params.1).await?;
+    let p3 = PE3::from_params(params.2).await?;
 
     Some((p1, p2, p3))
   }
@@ -1,7 +1,13 @@
 //! This module contains a set of utility functions to quickly send valid
 //! Actix responses from the endpoints.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use actix_web::http::header::{HeaderName, HeaderValue};
 
+use crate::htmx::{Swap, SwapSpec, Target};
+use crate::{TriggerEvent, TriggerPhase};
+
 pub use actix_web::HttpResponse;
 
 /// Sends an HttpResponse with an empty HTML body inside it
@@ -28,36 +34,340 @@ pub fn no_content() -> HttpResponse {
 
 /// Adds a HX-Redirect header to the response to perform a front-end redirect
 /// once the response is received.
-pub fn redirect(mut res: HttpResponse, target_url: &str) -> HttpResponse {
-  use std::str::FromStr;
+pub fn redirect(res: HttpResponse, target_url: &str) -> HttpResponse {
+  header(res, "HX-Redirect", target_url)
+}
 
-  if let Ok(value) = HeaderValue::from_str(target_url) {
-    // HeaderName::from_static(str) doesn't accept headers with uppercase letters
-    if let Ok(name) = HeaderName::from_str("HX-Redirect") {
-      let headers = res.headers_mut();
+/// Adds a HX-Location header to the response to perform a client-side redirect
+/// that doesn't reload the whole page, the new content is fetched with an AJAX
+/// request and swapped according to the [Location] options.
+///
+/// ```rs
+/// let res = responses::location(
+///   responses::no_content(),
+///   Location::new("/todos").target(Target::id("content")).swap(Swap::OuterHtml)
+/// );
+/// ```
+pub fn location(res: HttpResponse, location: Location) -> HttpResponse {
+  let value = match location.is_path_only() {
+    true => location.path,
+    false => ascii_json(&location).expect("HX-Location must serialize to JSON")
+  };
 
-      headers.append(name, value);
-    }
-  }
+  header(res, "HX-Location", &value)
+}
 
-  res
+/// Adds a HX-Push-Url header to the response to push the given url into the
+/// browser's history.
+pub fn push_url(res: HttpResponse, url: &str) -> HttpResponse {
+  header(res, "HX-Push-Url", url)
+}
+
+/// Adds a HX-Push-Url header that prevents the url of the request from being
+/// pushed into the browser's history.
+pub fn prevent_push_url(res: HttpResponse) -> HttpResponse {
+  header(res, "HX-Push-Url", "false")
+}
+
+/// Adds a HX-Replace-Url header to the response to replace the current url in
+/// the location bar, without creating a new history entry.
+pub fn replace_url(res: HttpResponse, url: &str) -> HttpResponse {
+  header(res, "HX-Replace-Url", url)
+}
+
+/// Adds a HX-Refresh header to the response to perform a full refresh of the
+/// page once the response is received.
+pub fn refresh(res: HttpResponse) -> HttpResponse {
+  header(res, "HX-Refresh", "true")
+}
+
+/// Adds a HX-Reswap header to the response to change how the response is
+/// swapped, overriding the hx-swap attribute of the element.
+pub fn reswap(res: HttpResponse, swap: impl Into<SwapSpec>) -> HttpResponse {
+  header(res, "HX-Reswap", &swap.into().to_string())
+}
+
+/// Adds a HX-Retarget header to the response to change the element the
+/// response is swapped into, overriding the hx-target attribute of the element.
+pub fn retarget(res: HttpResponse, target: &Target) -> HttpResponse {
+  header(res, "HX-Retarget", &target.to_string())
+}
+
+/// Adds a HX-Reselect header to the response to choose which part of the
+/// response is swapped in, overriding the hx-select attribute of the element.
+pub fn reselect(res: HttpResponse, selector: &str) -> HttpResponse {
+  header(res, "HX-Reselect", selector)
 }
 
 /// Modifies the supplied HttpResponse to append it a hx-trigger header for
-/// the given event.
+/// the given event, or one of the after-swap/after-settle headers depending on
+/// its [TriggerPhase].
+///
+/// The event is merged into the hx-trigger header if the response already has
+/// one, as HTMX only reads a single header. Refer to [TriggerEvent] for the
+/// format of the header.
 ///
 /// Can be used directly if needed, but using the [endpoints!](crate::endpoints) macro might offer better ergonomics.
-pub fn trigger(mut res: HttpResponse, event: &'static str) -> HttpResponse {
-  let headers = res.headers_mut();
+pub fn trigger(res: HttpResponse, event: impl Into<TriggerEvent>) -> HttpResponse {
+  triggers(res, [event.into()])
+}
+
+/// Same as [trigger] but for any number of events at once, each event is
+/// added to the header of its [TriggerPhase].
+pub fn triggers(
+  mut res: HttpResponse, events: impl IntoIterator<Item = TriggerEvent>
+) -> HttpResponse {
+  let events: Vec<TriggerEvent> = events.into_iter().collect();
 
-  headers.append(
-    HeaderName::from_static("hx-trigger"),
-    HeaderValue::from_static(event)
-  );
+  for phase in [
+    TriggerPhase::Receive,
+    TriggerPhase::AfterSwap,
+    TriggerPhase::AfterSettle
+  ] {
+    let phase_events = events
+      .iter()
+      .filter(|event| event.get_phase() == phase)
+      .cloned();
+
+    res = merge_events(res, phase.header(), phase_events);
+  }
 
   res
 }
 
+/// Same as [trigger] except the event is triggered after the settling step,
+/// once the new content has been swapped in and settled.
+pub fn trigger_after_settle(res: HttpResponse, event: impl Into<TriggerEvent>) -> HttpResponse {
+  trigger(res, event.into().phase(TriggerPhase::AfterSettle))
+}
+
+/// Same as [trigger] except the event is triggered after the swapping step,
+/// once the new content has been swapped in.
+pub fn trigger_after_swap(res: HttpResponse, event: impl Into<TriggerEvent>) -> HttpResponse {
+  trigger(res, event.into().phase(TriggerPhase::AfterSwap))
+}
+
+/// Adds the events to the ones already in the given header, events with an
+/// empty name are ignored while the detail of an event that is already in the
+/// header replaces the previous one.
+///
+/// A header that looks like JSON but can't be parsed is kept as is rather
+/// than losing its events, the new ones are then logged and left out.
+fn merge_events(
+  res: HttpResponse, name: &'static str, events: impl IntoIterator<Item = TriggerEvent>
+) -> HttpResponse {
+  let current = res
+    .headers()
+    .get(name)
+    .and_then(|value| value.to_str().ok())
+    .unwrap_or_default()
+    .trim();
+
+  let mut merged: Vec<(String, Option<serde_json::Value>)> = match serde_json::from_str::<
+    serde_json::Map<String, serde_json::Value>
+  >(current)
+  {
+    Ok(events) => events
+      .into_iter()
+      .map(|(name, detail)| (name, Some(detail).filter(|d| !d.is_null())))
+      .collect(),
+    Err(error) if current.starts_with('{') => {
+      let events: Vec<String> = events.into_iter().map(|e| e.name().to_owned()).collect();
+      log::error!(
+          "the {name} header `{current}` isn't valid JSON, the events {events:?} can't be merged into it: {error}"
+        );
+
+      return res;
+    }
+    Err(_) => current
+      .split(',')
+      .map(str::trim)
+      .filter(|event| !event.is_empty())
+      .map(|event| (event.to_owned(), None))
+      .collect()
+  };
+
+  for event in events {
+    let (event, detail) = event.into_parts();
+    let event = event.trim();
+
+    if event.is_empty() {
+      continue;
+    }
+
+    match merged.iter_mut().find(|(name, _)| name == event) {
+      Some((_, previous)) => *previous = detail.or(previous.take()),
+      None => merged.push((event.to_owned(), detail))
+    };
+  }
+
+  if merged.is_empty() {
+    return res;
+  }
+
+  // the names that aren't ASCII can only be sent escaped in the JSON form
+  let as_json = merged
+    .iter()
+    .any(|(name, detail)| detail.is_some() || !name.is_ascii());
+
+  let value = match as_json {
+    true => {
+      let events: serde_json::Map<String, serde_json::Value> = merged
+        .into_iter()
+        .map(|(name, detail)| (name, detail.unwrap_or_default()))
+        .collect();
+
+      // a map of JSON values always serializes
+      ascii_json(&events).unwrap_or_default()
+    }
+    false => merged
+      .into_iter()
+      .map(|(name, _)| name)
+      .collect::<Vec<_>>()
+      .join(", ")
+  };
+
+  header(res, name, &value)
+}
+
+/// Sets the given header on the response, invalid values like the ones with
+/// line breaks or non-ASCII characters are logged and left out.
+fn header(mut res: HttpResponse, name: &'static str, value: &str) -> HttpResponse {
+  // HeaderName::from_static(str) doesn't accept headers with uppercase letters
+  match (HeaderName::from_str(name), HeaderValue::from_str(value)) {
+    (Ok(name), Ok(value)) => {
+      res.headers_mut().insert(name, value);
+    }
+    (_, Err(error)) => log::error!("invalid value for the {name} header `{value}`: {error}"),
+    (Err(error), _) => log::error!("invalid header name `{name}`: {error}")
+  };
+
+  res
+}
+
+/// Serializes the value to JSON with its non-ASCII characters escaped as
+/// `\uXXXX`, since the header values can only contain ASCII.
+fn ascii_json(value: &impl serde::Serialize) -> serde_json::Result<String> {
+  let json = serde_json::to_string(value)?;
+  let mut escaped = String::with_capacity(json.len());
+
+  // outside of the strings JSON is only made of ASCII, so the characters that
+  // aren't can be escaped wherever they are
+  for c in json.chars() {
+    match c.is_ascii() {
+      true => escaped.push(c),
+      false => {
+        let mut units = [0; 2];
+        for unit in c.encode_utf16(&mut units) {
+          escaped.push_str(&format!("\\u{unit:04x}"));
+        }
+      }
+    }
+  }
+
+  Ok(escaped)
+}
+
+/// The options of a [location] response, serialized to the JSON form of the
+/// HX-Location header when anything else than the path is set.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct Location {
+  path: String,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  source: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  event: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  handler: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  target: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  swap: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  select: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  values: Option<serde_json::Value>,
+
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  headers: BTreeMap<String, String>
+}
+
+impl Location {
+  pub fn new(path: impl Into<String>) -> Self {
+    Self {
+      path: path.into(),
+      ..Default::default()
+    }
+  }
+
+  /// The source element of the request
+  pub fn source(mut self, selector: impl Into<String>) -> Self {
+    self.source = Some(selector.into());
+    self
+  }
+
+  /// An event that "triggered" the request
+  pub fn event(mut self, event: impl Into<String>) -> Self {
+    self.event = Some(event.into());
+    self
+  }
+
+  /// A callback that will handle the response HTML
+  pub fn handler(mut self, handler: impl Into<String>) -> Self {
+    self.handler = Some(handler.into());
+    self
+  }
+
+  /// The target to swap the response into
+  pub fn target(mut self, target: Target) -> Self {
+    self.target = Some(target.to_string());
+    self
+  }
+
+  /// How the response will be swapped in relative to the target
+  pub fn swap(mut self, swap: impl Into<SwapSpec>) -> Self {
+    self.swap = Some(swap.into().to_string());
+    self
+  }
+
+  /// Allows you to select the content you want swapped from a response
+  pub fn select(mut self, selector: impl Into<String>) -> Self {
+    self.select = Some(selector.into());
+    self
+  }
+
+  /// Values to submit with the request
+  pub fn values(mut self, values: &impl serde::Serialize) -> Self {
+    self.values =
+      Some(serde_json::to_value(values).expect("HX-Location values must serialize to JSON"));
+    self
+  }
+
+  /// Adds a header to submit with the request
+  pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.headers.insert(name.into(), value.into());
+    self
+  }
+
+  fn is_path_only(&self) -> bool {
+    self.source.is_none()
+      && self.event.is_none()
+      && self.handler.is_none()
+      && self.target.is_none()
+      && self.swap.is_none()
+      && self.select.is_none()
+      && self.values.is_none()
+      && self.headers.is_empty()
+  }
+}
+
 /// Send an oob alert message that can be used as a quick way to bubble up
 /// errors to the front-end.
 ///
@@ -76,3 +386,162 @@ pub fn alert(class: &str, message: &impl maud::Render) -> maud::Markup {
     }
   )
 }
+
+/// Renders the markup as an out-of-band swap targeting the element with the
+/// given id, so a single response can update several parts of the page at once:
+/// ```rs
+/// TodoList::render(&todos)
+///   .join(responses::oob(html!((todos.len())), "span", "todo-count", Swap::InnerHtml))
+///   .into_response()
+/// ```
+///
+/// The markup is rendered inside a `tag` element carrying the `hx-swap-oob`
+/// attribute. With [Swap::OuterHtml] that element replaces the target, so the
+/// tag should be the one of the target, like `ul` or `tr`. [Swap::Delete]
+/// removes the target and ignores the markup, while the other strategies
+/// insert the content of the element relative to the target, in which case
+/// the tag should be a valid parent for the markup, like `tbody` for rows.
+pub fn oob(markup: maud::Markup, tag: &'static str, id: &str, swap: Swap) -> maud::Markup {
+  let swap_oob = swap.as_str();
+
+  match swap {
+    Swap::OuterHtml => crate::htmx::element(
+      tag,
+      [("id", id.to_owned()), ("hx-swap-oob", swap_oob.to_owned())],
+      markup
+    ),
+    Swap::Delete => crate::htmx::element(
+      tag,
+      [("id", id.to_owned()), ("hx-swap-oob", swap_oob.to_owned())],
+      maud::html!()
+    ),
+    _ => crate::htmx::element(tag, [("hx-swap-oob", format!("{swap_oob}:#{id}"))], markup)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn header_of(res: &HttpResponse, name: &str) -> Option<String> {
+    res
+      .headers()
+      .get(name)
+      .map(|value| value.to_str().unwrap().to_owned())
+  }
+
+  #[test]
+  fn events_without_payload_are_listed() {
+    let res = triggers(no_content(), ["reload".into(), "close".into()]);
+    let res = trigger(res, "reload");
+
+    assert_eq!(header_of(&res, "HX-Trigger").unwrap(), "reload, close");
+  }
+
+  #[test]
+  fn events_with_payload_are_merged_to_json() {
+    let res = trigger(no_content(), "reload");
+    let res = trigger(res, TriggerEvent::new("updated").field("id", &3).unwrap());
+    let res = trigger(res, TriggerEvent::new("updated").field("id", &4).unwrap());
+
+    let value: serde_json::Value =
+      serde_json::from_str(&header_of(&res, "HX-Trigger").unwrap()).unwrap();
+    assert_eq!(
+      value,
+      serde_json::json!({ "reload": null, "updated": { "id": 4 } })
+    );
+  }
+
+  #[test]
+  fn events_are_merged_into_the_existing_header() {
+    let res = HttpResponse::Ok()
+      .insert_header(("HX-Trigger", "close , reload"))
+      .finish();
+    let res = triggers(res, ["reload".into(), "open".into()]);
+    assert_eq!(
+      header_of(&res, "HX-Trigger").unwrap(),
+      "close, reload, open"
+    );
+
+    let res = HttpResponse::Ok()
+      .insert_header(("HX-Trigger", r#"{"updated": {"id": 3}}"#))
+      .finish();
+    let res = trigger(res, "reload");
+    assert_eq!(
+      header_of(&res, "HX-Trigger").unwrap(),
+      r#"{"reload":null,"updated":{"id":3}}"#
+    );
+  }
+
+  #[test]
+  fn invalid_json_header_is_kept() {
+    let res = HttpResponse::Ok()
+      .insert_header(("HX-Trigger", r#"{"updated": "#))
+      .finish();
+    let res = trigger(res, "reload");
+
+    assert_eq!(header_of(&res, "HX-Trigger").unwrap(), r#"{"updated": "#);
+  }
+
+  #[test]
+  fn events_are_split_by_phase() {
+    let res = triggers(
+      no_content(),
+      [
+        TriggerEvent::new("a"),
+        TriggerEvent::new("b").phase(TriggerPhase::AfterSwap),
+        TriggerEvent::new("c").phase(TriggerPhase::AfterSettle)
+      ]
+    );
+
+    assert_eq!(header_of(&res, "HX-Trigger").unwrap(), "a");
+    assert_eq!(header_of(&res, "HX-Trigger-After-Swap").unwrap(), "b");
+    assert_eq!(header_of(&res, "HX-Trigger-After-Settle").unwrap(), "c");
+  }
+
+  #[test]
+  fn non_ascii_is_escaped() {
+    let res = trigger(
+      no_content(),
+      TriggerEvent::new("saved").detail(&"café 🎉").unwrap()
+    );
+    let value = header_of(&res, "HX-Trigger").unwrap();
+
+    assert_eq!(value, r#"{"saved":"caf\u00e9 \ud83c\udf89"}"#);
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&value).unwrap(),
+      serde_json::json!({ "saved": "café 🎉" })
+    );
+
+    let res = trigger(no_content(), "été");
+    assert_eq!(
+      header_of(&res, "HX-Trigger").unwrap(),
+      r#"{"\u00e9t\u00e9":null}"#
+    );
+  }
+
+  #[test]
+  fn oob_renders_the_given_tag() {
+    let row = maud::html!(td { "a" });
+
+    assert_eq!(
+      oob(row.clone(), "tr", "row-1", Swap::OuterHtml).into_string(),
+      r#"<tr id="row-1" hx-swap-oob="outerHTML"><td>a</td></tr>"#
+    );
+    assert_eq!(
+      oob(row.clone(), "tr", "row-1", Swap::Delete).into_string(),
+      r#"<tr id="row-1" hx-swap-oob="delete"></tr>"#
+    );
+    assert_eq!(
+      oob(maud::html!(tr { (row) }), "tbody", "rows", Swap::BeforeEnd).into_string(),
+      r#"<tbody hx-swap-oob="beforeend:#rows"><tr><td>a</td></tr></tbody>"#
+    );
+  }
+
+  #[test]
+  fn invalid_header_values_are_left_out() {
+    let res = redirect(no_content(), "/a\nb");
+
+    assert!(header_of(&res, "HX-Redirect").is_none());
+  }
+}
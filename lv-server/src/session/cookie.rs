@@ -0,0 +1,163 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_web::cookie::{Cookie, CookieJar, Key};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use super::{SessionState, SessionStore};
+
+/// The name the values are signed or encrypted with, it's unrelated to the
+/// name of the session cookie.
+const NAME: &str = "lv-session";
+
+/// Browsers ignore the cookies that are larger than 4KB
+const MAX_SIZE: usize = 4096;
+
+/// Keeps the sessions in the session cookie itself, so no storage is needed on
+/// the server. The state is either encrypted or only signed, in which case the
+/// client can read it but not alter it.
+///
+/// The server can't revoke these sessions: a copy of the cookie stays valid
+/// until its expiry even once the session was renewed or purged. A store that
+/// keeps the sessions on the server, like the [MemoryStore](super::MemoryStore),
+/// is needed when a logout must end the session for good.
+///
+/// The key should come from the configuration of the server so the sessions
+/// survive its restarts:
+/// ```rs
+/// CookieStore::encrypted(Key::from(&secret))
+/// ```
+pub struct CookieStore {
+  key: Key,
+  encrypted: bool,
+  ttl: Duration
+}
+
+/// The state as it's sealed in the cookie, along with its expiry as a unix
+/// timestamp so a stolen cookie can't be replayed forever.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Sealed<S> {
+  expires: u64,
+  state: S
+}
+
+impl CookieStore {
+  pub fn encrypted(key: Key) -> Self {
+    Self {
+      key,
+      encrypted: true,
+      ttl: Duration::from_secs(24 * 60 * 60)
+    }
+  }
+
+  pub fn signed(key: Key) -> Self {
+    Self {
+      encrypted: false,
+      ..Self::encrypted(key)
+    }
+  }
+
+  /// How long the sessions are valid after their last change, 24 hours by
+  /// default.
+  pub fn ttl(mut self, ttl: Duration) -> Self {
+    self.ttl = ttl;
+    self
+  }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for CookieStore {
+  async fn load(&self, key: &str) -> Option<SessionState> {
+    let mut jar = CookieJar::new();
+    jar.add_original(Cookie::new(NAME, key.to_owned()));
+
+    let state = match self.encrypted {
+      true => jar.private(&self.key).get(NAME)?.value().to_owned(),
+      false => {
+        let cookie = jar.signed(&self.key).get(NAME)?;
+        String::from_utf8(URL_SAFE_NO_PAD.decode(cookie.value()).ok()?).ok()?
+      }
+    };
+
+    let sealed: Sealed<SessionState> = serde_json::from_str(&state).ok()?;
+
+    (sealed.expires > now()).then_some(sealed.state)
+  }
+
+  async fn save(&self, _: Option<&str>, state: &SessionState) -> actix_web::Result<String> {
+    let state = serde_json::to_string(&Sealed {
+      expires: now() + self.ttl.as_secs(),
+      state
+    })?;
+
+    let mut jar = CookieJar::new();
+    match self.encrypted {
+      true => jar.private_mut(&self.key).add(Cookie::new(NAME, state)),
+      // the JSON isn't a valid cookie value on its own
+      false => jar
+        .signed_mut(&self.key)
+        .add(Cookie::new(NAME, URL_SAFE_NO_PAD.encode(state)))
+    };
+
+    let value = jar
+      .get(NAME)
+      .map(|cookie| cookie.value().to_owned())
+      .unwrap_or_default();
+
+    match value.len() > MAX_SIZE {
+      true => Err(actix_web::error::ErrorInternalServerError(
+        "the session is too large to fit in a cookie"
+      )),
+      false => Ok(value)
+    }
+  }
+
+  /// The cookie is removed from the client, but the state can't be revoked as
+  /// it only lives in the cookie.
+  async fn delete(&self, _: &str) {}
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn state() -> SessionState {
+    SessionState::from([("user".to_owned(), "\"alice\"".to_owned())])
+  }
+
+  #[tokio::test]
+  async fn round_trip() {
+    for store in [
+      CookieStore::encrypted(Key::generate()),
+      CookieStore::signed(Key::generate())
+    ] {
+      let key = store.save(None, &state()).await.unwrap();
+
+      assert_eq!(store.load(&key).await, Some(state()));
+    }
+  }
+
+  #[tokio::test]
+  async fn expired_state_is_rejected() {
+    let store = CookieStore::encrypted(Key::generate()).ttl(Duration::ZERO);
+    let key = store.save(None, &state()).await.unwrap();
+
+    assert_eq!(store.load(&key).await, None);
+  }
+
+  #[tokio::test]
+  async fn state_of_another_key_is_rejected() {
+    let store = CookieStore::signed(Key::generate());
+    let key = store.save(None, &state()).await.unwrap();
+
+    assert_eq!(CookieStore::signed(Key::generate()).load(&key).await, None);
+    assert_eq!(store.load(&key[1..]).await, None);
+  }
+}
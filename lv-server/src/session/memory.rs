@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use super::{SessionState, SessionStore};
+
+/// Keeps the sessions in the memory of the server, they are lost as soon as it
+/// restarts and aren't shared between several instances of the server.
+///
+/// A session expires once it hasn't been used for the [ttl](MemoryStore::ttl),
+/// the expired sessions are removed as they are loaded and the ones that are
+/// never loaded again by a sweep of the whole store, at most once per ttl.
+pub struct MemoryStore {
+  sessions: Mutex<Entries>,
+  ttl: Duration
+}
+
+struct Entries {
+  states: HashMap<String, (SessionState, Instant)>,
+  next_sweep: Instant
+}
+
+impl Default for MemoryStore {
+  fn default() -> Self {
+    let ttl = Duration::from_secs(24 * 60 * 60);
+
+    Self {
+      sessions: Mutex::new(Entries {
+        states: HashMap::new(),
+        next_sweep: Instant::now() + ttl
+      }),
+      ttl
+    }
+  }
+}
+
+impl MemoryStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// How long the sessions are kept after their last use, 24 hours by
+  /// default.
+  pub fn ttl(mut self, ttl: Duration) -> Self {
+    self.ttl = ttl;
+    self.sessions.get_mut().unwrap().next_sweep = Instant::now() + ttl;
+    self
+  }
+}
+
+#[async_trait::async_trait]
+impl SessionStore for MemoryStore {
+  async fn load(&self, key: &str) -> Option<SessionState> {
+    let mut sessions = self.sessions.lock().unwrap();
+    let now = Instant::now();
+
+    match sessions.states.get_mut(key) {
+      Some((state, expires)) if *expires > now => {
+        *expires = now + self.ttl;
+        Some(state.clone())
+      }
+      Some(_) => {
+        sessions.states.remove(key);
+        None
+      }
+      None => None
+    }
+  }
+
+  async fn save(&self, key: Option<&str>, state: &SessionState) -> actix_web::Result<String> {
+    let key = key
+      .map(str::to_owned)
+      .unwrap_or_else(|| URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>()));
+
+    let mut sessions = self.sessions.lock().unwrap();
+    let now = Instant::now();
+
+    if sessions.next_sweep <= now {
+      sessions.states.retain(|_, (_, expires)| *expires > now);
+      sessions.next_sweep = now + self.ttl;
+    }
+
+    sessions
+      .states
+      .insert(key.clone(), (state.clone(), now + self.ttl));
+
+    Ok(key)
+  }
+
+  async fn delete(&self, key: &str) {
+    self.sessions.lock().unwrap().states.remove(key);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn state() -> SessionState {
+    SessionState::from([("user".to_owned(), "\"alice\"".to_owned())])
+  }
+
+  #[actix_web::test]
+  async fn loading_a_session_extends_it() {
+    let store = MemoryStore::new().ttl(Duration::from_millis(200));
+    let key = store.save(None, &state()).await.unwrap();
+
+    for _ in 0..3 {
+      std::thread::sleep(Duration::from_millis(120));
+      assert_eq!(store.load(&key).await, Some(state()));
+    }
+
+    std::thread::sleep(Duration::from_millis(250));
+    assert_eq!(store.load(&key).await, None);
+    assert!(store.sessions.lock().unwrap().states.is_empty());
+  }
+
+  #[actix_web::test]
+  async fn expired_sessions_are_swept() {
+    let store = MemoryStore::new().ttl(Duration::from_millis(50));
+    store.save(None, &state()).await.unwrap();
+    store.save(None, &state()).await.unwrap();
+
+    std::thread::sleep(Duration::from_millis(60));
+    store.save(None, &state()).await.unwrap();
+
+    assert_eq!(store.sessions.lock().unwrap().states.len(), 1);
+  }
+}
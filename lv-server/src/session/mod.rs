@@ -0,0 +1,551 @@
+//! User sessions, kept in a [SessionStore] between the requests and accessed
+//! through the [Session] extractor.
+//!
+//! The sessions are enabled by adding [Sessions] to the app data along with
+//! the [middleware] that loads and saves them:
+//! ```rs
+//! App::new()
+//!   .app_data(Sessions::new(MemoryStore::new()))
+//!   .wrap(actix_web::middleware::from_fn(session::middleware))
+//! ```
+//!
+//! Any handler can then extract the session, including the ones generated by
+//! the [endpoints!](crate::endpoints) macro:
+//! ```rs
+//! impl api::post_login::Router {
+//!   pub async fn endpoint(
+//!     session: Session, Form(form): Form<LoginForm>
+//!   ) -> actix_web::Result<HttpResponse> {
+//!     session.renew();
+//!     session.insert("user", &form.username)?;
+//!     session.flash("success", "Welcome back");
+//!
+//!     Ok(responses::redirect(responses::no_content(), "/"))
+//!   }
+//! }
+//! ```
+//!
+//! When the [CSRF token mode](crate::csrf::CsrfTokens) is enabled, the tokens
+//! are bound to the session rather than to their own cookie.
+use std::cell::RefCell;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::HttpMessage;
+
+mod cookie;
+mod memory;
+mod store;
+pub use cookie::CookieStore;
+pub use memory::MemoryStore;
+pub use store::{SessionState, SessionStore};
+
+pub use actix_web::cookie::Key;
+
+/// The key the flash messages are kept under in the session
+const FLASH_KEY: &str = "_lv_flash";
+
+/// The store of the sessions and the settings of their cookie, to add to the
+/// app data for the [middleware] to use:
+/// ```rs
+/// App::new().app_data(Sessions::new(CookieStore::encrypted(key)).cookie_name("session"))
+/// ```
+#[derive(Clone)]
+pub struct Sessions {
+  store: Arc<dyn SessionStore>,
+  cookie_name: String,
+  secure: bool
+}
+
+impl Sessions {
+  pub fn new(store: impl SessionStore) -> Self {
+    Self {
+      store: Arc::new(store),
+      cookie_name: "lv-session".to_owned(),
+      secure: true
+    }
+  }
+
+  /// The name of the session cookie, `lv-session` by default
+  pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+    self.cookie_name = name.into();
+    self
+  }
+
+  /// Whether the session cookie is only sent over HTTPS, enabled by default
+  pub fn secure(mut self, secure: bool) -> Self {
+    self.secure = secure;
+    self
+  }
+
+  fn cookie(&self, value: String) -> actix_web::cookie::Cookie<'static> {
+    actix_web::cookie::Cookie::build(self.cookie_name.clone(), value)
+      .path("/")
+      .http_only(true)
+      .secure(self.secure)
+      .same_site(actix_web::cookie::SameSite::Lax)
+      .finish()
+  }
+}
+
+/// Loads the session of the request from the store of the [Sessions] in the
+/// app data, then saves it once the response is ready if it was changed.
+///
+/// The requests go through untouched if there are no [Sessions] in the app
+/// data.
+pub async fn middleware<B: MessageBody + 'static>(
+  req: ServiceRequest, next: Next<B>
+) -> Result<ServiceResponse<B>, actix_web::Error> {
+  let Some(sessions) = req.app_data::<Sessions>().cloned() else {
+    return next.call(req).await;
+  };
+
+  let key = req
+    .cookie(&sessions.cookie_name)
+    .map(|cookie| cookie.value().to_owned());
+  let state = match &key {
+    Some(key) => sessions.store.load(key).await,
+    None => None
+  };
+  let has_cookie = key.is_some();
+  // the key of a session that is gone isn't reused
+  let key = key.filter(|_| state.is_some());
+
+  let session = Session(Rc::new(RefCell::new(Inner {
+    state: state.unwrap_or_default(),
+    status: Status::Unchanged
+  })));
+  req.extensions_mut().insert(session.clone());
+
+  let mut res = next.call(req).await?;
+
+  let Inner { state, status } = session.0.take();
+  let cookie = match (status, key) {
+    // the cookie of a session that is unknown or has expired is removed
+    (Status::Unchanged, None) => {
+      if has_cookie {
+        res
+          .response_mut()
+          .add_removal_cookie(&sessions.cookie(String::new()))?;
+      }
+
+      None
+    }
+    (Status::Unchanged, Some(_)) => None,
+    (Status::Changed, key) => Some(sessions.store.save(key.as_deref(), &state).await?),
+    (Status::Renewed, key) => {
+      if let Some(key) = key {
+        sessions.store.delete(&key).await;
+      }
+
+      Some(sessions.store.save(None, &state).await?)
+    }
+    (Status::Purged, key) => {
+      if let Some(key) = key {
+        sessions.store.delete(&key).await;
+      }
+
+      if has_cookie {
+        res
+          .response_mut()
+          .add_removal_cookie(&sessions.cookie(String::new()))?;
+      }
+
+      None
+    }
+  };
+
+  if let Some(cookie) = cookie {
+    res.response_mut().add_cookie(&sessions.cookie(cookie))?;
+  }
+
+  Ok(res)
+}
+
+/// The session of the request, the changes are saved by the [middleware] once
+/// the response is ready. Cloning a session is cheap and all the clones share
+/// the same state.
+///
+/// The values are serialized to JSON, reading a value with another type than
+/// the one it was inserted with returns `None`.
+#[derive(Clone)]
+pub struct Session(Rc<RefCell<Inner>>);
+
+#[derive(Default)]
+struct Inner {
+  state: SessionState,
+  status: Status
+}
+
+#[derive(Default, Clone, Copy)]
+enum Status {
+  #[default]
+  Unchanged,
+  Changed,
+  /// The session is saved under a new key, and the old one is deleted
+  Renewed,
+  /// The session is deleted along with its cookie
+  Purged
+}
+
+impl Session {
+  pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+    let inner = self.0.borrow();
+
+    inner
+      .state
+      .get(key)
+      .and_then(|value| serde_json::from_str(value).ok())
+  }
+
+  pub fn insert(
+    &self, key: impl Into<String>, value: &impl serde::Serialize
+  ) -> serde_json::Result<()> {
+    let value = serde_json::to_string(value)?;
+
+    let mut inner = self.0.borrow_mut();
+    inner.state.insert(key.into(), value);
+    inner.changed();
+
+    Ok(())
+  }
+
+  pub fn remove(&self, key: &str) {
+    let mut inner = self.0.borrow_mut();
+
+    if inner.state.remove(key).is_some() {
+      inner.changed();
+    }
+  }
+
+  /// Removes every value from the session while keeping its key
+  pub fn clear(&self) {
+    let mut inner = self.0.borrow_mut();
+    inner.state.clear();
+    inner.changed();
+  }
+
+  /// Saves the session under a new key, it should be called whenever the
+  /// privileges of the user change, like when they log in, so a key that was
+  /// stolen before doesn't get them.
+  pub fn renew(&self) {
+    let mut inner = self.0.borrow_mut();
+
+    if !matches!(inner.status, Status::Purged) {
+      inner.status = Status::Renewed;
+    }
+  }
+
+  /// Deletes the session and its cookie, like when the user logs out
+  pub fn purge(&self) {
+    let mut inner = self.0.borrow_mut();
+    inner.state.clear();
+    inner.status = Status::Purged;
+  }
+
+  /// Adds a message to show on the next page the user loads, using the
+  /// [alert](crate::responses::alert) classes.
+  pub fn flash(&self, class: impl Into<String>, message: impl Into<String>) {
+    let mut flashes: Vec<Flash> = self.get(FLASH_KEY).unwrap_or_default();
+    flashes.push(Flash {
+      class: class.into(),
+      message: message.into()
+    });
+
+    // the flashes only contain strings, they always serialize
+    let _ = self.insert(FLASH_KEY, &flashes);
+  }
+
+  /// Removes the flash messages from the session and returns them
+  pub fn take_flashes(&self) -> Flashes {
+    let flashes = self.get(FLASH_KEY).unwrap_or_default();
+    self.remove(FLASH_KEY);
+
+    Flashes(flashes)
+  }
+}
+
+impl Inner {
+  fn changed(&mut self) {
+    if let Status::Unchanged = self.status {
+      self.status = Status::Changed;
+    }
+  }
+}
+
+impl actix_web::FromRequest for Session {
+  type Error = actix_web::Error;
+
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    let session = req.extensions().get::<Session>().cloned().ok_or_else(|| {
+      actix_web::error::ErrorInternalServerError("the session middleware is missing from the app")
+    });
+
+    ready(session)
+  }
+}
+
+/// A message kept in the [Session] until the next page shows it, refer to
+/// [Flashes] for their rendering.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Flash {
+  class: String,
+  message: String
+}
+
+impl Flash {
+  pub fn class(&self) -> &str {
+    &self.class
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+/// The flash messages taken from the [Session], they render as a single
+/// [alert](crate::responses::alert) as the page only has one alert element.
+/// The alert has the classes of all the messages, while each message is in
+/// its own `p` with its class:
+/// ```rs
+/// html!((session.take_flashes()))
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Flashes(Vec<Flash>);
+
+impl std::ops::Deref for Flashes {
+  type Target = [Flash];
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl IntoIterator for Flashes {
+  type Item = Flash;
+  type IntoIter = std::vec::IntoIter<Flash>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.into_iter()
+  }
+}
+
+impl maud::Render for Flashes {
+  fn render(&self) -> maud::Markup {
+    if self.0.is_empty() {
+      return maud::html!();
+    }
+
+    let mut classes: Vec<&str> = Vec::new();
+    for flash in &self.0 {
+      if !classes.contains(&flash.class()) {
+        classes.push(flash.class());
+      }
+    }
+
+    let messages = maud::html!(
+      @for flash in &self.0 {
+        p class=(flash.class) { (flash.message) }
+      }
+    );
+
+    crate::responses::alert(&classes.join(" "), &messages)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::cookie::{time, Cookie};
+  use actix_web::test::{self, TestRequest};
+  use actix_web::{web, App, HttpResponse};
+
+  use super::*;
+
+  fn sessions() -> Sessions {
+    Sessions::new(MemoryStore::new()).secure(false)
+  }
+
+  /// Sends a request to an app whose routes act on the session, returns the
+  /// session cookie of the response along with its body.
+  async fn call(
+    sessions: &Sessions, path: &str, cookie: Option<&Cookie<'static>>
+  ) -> (Option<Cookie<'static>>, String) {
+    let app = test::init_service(
+      App::new()
+        .app_data(sessions.clone())
+        .wrap(actix_web::middleware::from_fn(middleware))
+        .route(
+          "/read",
+          web::get().to(|session: Session| async move {
+            HttpResponse::Ok().body(session.get::<String>("user").unwrap_or_default())
+          })
+        )
+        .route(
+          "/insert",
+          web::get().to(|session: Session| async move {
+            session.insert("user", &"alice")?;
+            Ok::<_, actix_web::Error>(HttpResponse::Ok().finish())
+          })
+        )
+        .route(
+          "/renew",
+          web::get().to(|session: Session| async move {
+            session.renew();
+            HttpResponse::Ok().finish()
+          })
+        )
+        .route(
+          "/purge",
+          web::get().to(|session: Session| async move {
+            session.purge();
+            HttpResponse::Ok().finish()
+          })
+        )
+        .route(
+          "/flash",
+          web::get().to(|session: Session| async move {
+            session.flash("success", "saved");
+            HttpResponse::Ok().finish()
+          })
+        )
+        .route(
+          "/flashes",
+          web::get().to(|session: Session| async move {
+            let flashes = session.take_flashes();
+            let messages: Vec<&str> = flashes.iter().map(Flash::message).collect();
+
+            HttpResponse::Ok().body(messages.join(","))
+          })
+        )
+    )
+    .await;
+
+    let req = cookie
+      .into_iter()
+      .fold(TestRequest::with_uri(path), |req, cookie| {
+        req.cookie(cookie.clone())
+      });
+    let res = test::call_service(&app, req.to_request()).await;
+
+    let cookie = res
+      .response()
+      .cookies()
+      .find(|cookie| cookie.name() == "lv-session")
+      .map(|cookie| cookie.into_owned());
+    let body = test::read_body(res).await;
+
+    (cookie, String::from_utf8(body.to_vec()).unwrap())
+  }
+
+  fn is_removal(cookie: &Cookie) -> bool {
+    cookie.value().is_empty() && cookie.max_age() == Some(time::Duration::ZERO)
+  }
+
+  #[actix_web::test]
+  async fn unchanged_session_sets_no_cookie() {
+    let sessions = sessions();
+    assert_eq!(call(&sessions, "/read", None).await, (None, String::new()));
+
+    let (cookie, _) = call(&sessions, "/insert", None).await;
+    let (unchanged, body) = call(&sessions, "/read", cookie.as_ref()).await;
+    assert!(unchanged.is_none());
+    assert_eq!(body, "alice");
+  }
+
+  #[actix_web::test]
+  async fn changed_session_sets_the_cookie() {
+    let sessions = sessions();
+
+    let (cookie, _) = call(&sessions, "/insert", None).await;
+    let cookie = cookie.expect("a new session sets its cookie");
+    assert!(!cookie.value().is_empty());
+    assert!(cookie.http_only().unwrap_or_default());
+
+    // the key of an existing session is kept
+    let (changed, _) = call(&sessions, "/insert", Some(&cookie)).await;
+    assert_eq!(changed.unwrap().value(), cookie.value());
+  }
+
+  #[actix_web::test]
+  async fn renewed_session_gets_a_new_key() {
+    let sessions = sessions();
+    let (cookie, _) = call(&sessions, "/insert", None).await;
+    let cookie = cookie.unwrap();
+
+    let (renewed, _) = call(&sessions, "/renew", Some(&cookie)).await;
+    let renewed = renewed.expect("a renewed session sets its cookie");
+    assert_ne!(renewed.value(), cookie.value());
+
+    assert_eq!(call(&sessions, "/read", Some(&renewed)).await.1, "alice");
+    assert_eq!(call(&sessions, "/read", Some(&cookie)).await.1, "");
+  }
+
+  #[actix_web::test]
+  async fn purged_session_removes_the_cookie() {
+    let sessions = sessions();
+    let (cookie, _) = call(&sessions, "/insert", None).await;
+    let cookie = cookie.unwrap();
+
+    let (removal, _) = call(&sessions, "/purge", Some(&cookie)).await;
+    assert!(is_removal(&removal.expect("the cookie is removed")));
+    assert_eq!(call(&sessions, "/read", Some(&cookie)).await.1, "");
+
+    // there is no cookie to remove without a session
+    assert_eq!(call(&sessions, "/purge", None).await.0, None);
+  }
+
+  #[actix_web::test]
+  async fn unknown_session_removes_the_cookie() {
+    let sessions = sessions();
+    let unknown = Cookie::new("lv-session", "unknown");
+
+    let (removal, _) = call(&sessions, "/read", Some(&unknown)).await;
+    assert!(is_removal(&removal.expect("the cookie is removed")));
+
+    // a new session replaces the unknown one
+    let (cookie, _) = call(&sessions, "/insert", Some(&unknown)).await;
+    let cookie = cookie.unwrap();
+    assert!(!is_removal(&cookie));
+    assert_ne!(cookie.value(), "unknown");
+  }
+
+  #[test]
+  fn flashes_render_a_single_alert() {
+    use maud::Render;
+
+    let flash = |class: &str, message: &str| Flash {
+      class: class.to_owned(),
+      message: message.to_owned()
+    };
+    let flashes = Flashes(vec![
+      flash("success", "saved"),
+      flash("error", "<not sent>"),
+      flash("success", "done"),
+    ]);
+
+    let html = flashes.render().into_string();
+    assert_eq!(html.matches(r#"id="lv-alert""#).count(), 1);
+    assert!(html.contains(r#"class="success error""#));
+    assert!(html.contains(
+      r#"<p class="success">saved</p><p class="error">&lt;not sent&gt;</p><p class="success">done</p>"#
+    ));
+
+    assert_eq!(Flashes::default().render().into_string(), "");
+  }
+
+  #[actix_web::test]
+  async fn flashes_are_consumed_once() {
+    let sessions = sessions();
+    let (cookie, _) = call(&sessions, "/flash", None).await;
+    let cookie = cookie.unwrap();
+
+    assert_eq!(call(&sessions, "/flashes", Some(&cookie)).await.1, "saved");
+    assert_eq!(call(&sessions, "/flashes", Some(&cookie)).await.1, "");
+  }
+}
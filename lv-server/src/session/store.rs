@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+/// The values of a session, serialized to JSON
+pub type SessionState = HashMap<String, String>;
+
+/// Where the [Sessions](super::Sessions) are kept between the requests, the
+/// crate offers a [CookieStore](super::CookieStore) and a
+/// [MemoryStore](super::MemoryStore) but any storage can be used:
+/// ```rs
+/// #[async_trait::async_trait]
+/// impl SessionStore for RedisStore {
+///   async fn load(&self, key: &str) -> Option<SessionState> {
+///     self.client.get(key).await.ok()
+///   }
+///
+///   async fn save(&self, key: Option<&str>, state: &SessionState) -> actix_web::Result<String> {
+///     let key = key.map(str::to_owned).unwrap_or_else(generate_key);
+///     self.client.set(&key, state).await.map_err(ErrorInternalServerError)?;
+///
+///     Ok(key)
+///   }
+///
+///   async fn delete(&self, key: &str) {
+///     let _ = self.client.del(key).await;
+///   }
+/// }
+/// ```
+///
+/// The key is the value of the session cookie, it's up to the store to decide
+/// what it contains.
+#[async_trait::async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+  /// The state of the session, or `None` if the key is unknown or expired
+  async fn load(&self, key: &str) -> Option<SessionState>;
+
+  /// Saves the state of the session and returns its key, `key` is `None` for
+  /// the new sessions and the renewed ones.
+  async fn save(&self, key: Option<&str>, state: &SessionState) -> actix_web::Result<String>;
+
+  async fn delete(&self, key: &str);
+}
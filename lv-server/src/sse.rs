@@ -0,0 +1,448 @@
+//! Server-Sent Events support, to push events and fragments to the clients
+//! outside of the responses to their own requests.
+//!
+//! An endpoint is declared with the `SSE` verb and responds with a subscription
+//! to the [Hub]:
+//! ```rs
+//! lv_server::endpoints!(TodoList {
+//!   stream => SSE "/stream"
+//! });
+//!
+//! impl api::stream::Router {
+//!   pub async fn endpoint(hub: Hub) -> HttpResponse {
+//!     hub.response()
+//!   }
+//! }
+//! ```
+//!
+//! The handlers can then publish events or markup to every connected client:
+//! ```rs
+//! hub.publish_event(TodoList::emit(TodoListEvents::Reload));
+//! ```
+//!
+//! The client side relies on the [HTMX SSE extension](https://htmx.org/extensions/sse/),
+//! the elements listening to the events must be children of the element that
+//! connects to the endpoint:
+//! ```rs
+//! div hx-ext="sse" sse-connect=(api::stream::sse_connect()) {
+//!   div hx-trigger=(sse::listen(TodoListEvents::RELOAD)) {}
+//! }
+//! ```
+use std::convert::Infallible;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+use crate::{EventListener, WithTrigger};
+
+/// A [Trigger](crate::htmx::Trigger) for an event published on a [Hub], as
+/// opposed to the events sent in the response headers. The modifiers of the
+/// event's declaration are left out as they don't apply to the SSE events.
+pub fn listen<Events>(event: EventListener<Events>) -> crate::htmx::Trigger {
+  crate::htmx::Trigger::event(event.event_name()).sse()
+}
+
+/// The value of the `sse-swap` attribute for the markup published under the
+/// given name with [Hub::publish_markup], its line breaks are removed like in
+/// [Message::event] so both sides agree on the name:
+/// ```rs
+/// ul sse-swap=(sse::sse_swap("todos")) {}
+/// ```
+pub fn sse_swap(name: &str) -> String {
+  single_line(name.to_owned())
+}
+
+/// Broadcasts [Messages](Message) to all of its subscribers. Cloning a hub is
+/// cheap and all the clones share the same subscribers.
+///
+/// The hub is expected to be added to the app data of the Actix app, it can
+/// then be extracted in the handlers:
+/// ```rs
+/// App::new().app_data(hub.clone())
+/// ```
+///
+/// The messages are buffered for each subscriber up to the capacity of the
+/// hub, a subscriber that falls further behind skips the oldest messages
+/// rather than letting the buffer grow.
+#[derive(Clone)]
+pub struct Hub {
+  sender: broadcast::Sender<Message>
+}
+
+impl Hub {
+  /// The number of messages buffered for each subscriber by default
+  pub const CAPACITY: usize = 256;
+
+  pub fn new() -> Self {
+    Self::with_capacity(Self::CAPACITY)
+  }
+
+  /// A hub that buffers up to `capacity` messages for each subscriber
+  ///
+  /// # Panics
+  /// If the capacity is 0.
+  pub fn with_capacity(capacity: usize) -> Self {
+    let (sender, _) = broadcast::channel(capacity);
+
+    Self { sender }
+  }
+
+  /// Registers a new subscriber that receives every message published after
+  /// this call, can be used as an in-process client.
+  pub fn subscribe(&self) -> Subscription {
+    Subscription {
+      receiver: self.sender.subscribe()
+    }
+  }
+
+  /// A streaming `text/event-stream` response for a new subscriber, to be
+  /// returned by the `SSE` endpoints.
+  pub fn response(&self) -> HttpResponse {
+    HttpResponse::Ok()
+      .content_type("text/event-stream")
+      .insert_header(("Cache-Control", "no-cache"))
+      .streaming(EventStream::new(self.subscribe()))
+  }
+
+  /// Sends the message to every subscriber, returns the number of subscribers
+  /// that will receive it.
+  pub fn publish(&self, message: Message) -> usize {
+    // sending only fails when there are no subscribers
+    self.sender.send(message).unwrap_or(0)
+  }
+
+  /// Publishes the event under its name, with its detail serialized to JSON
+  /// as the data of the message. An event that can't be serialized is logged
+  /// and isn't published.
+  pub fn publish_event(&self, event: impl WithTrigger) -> usize {
+    let (name, detail) = match event.into_trigger() {
+      Ok(event) => event.into_parts(),
+      Err(error) => {
+        log::error!("the detail of an event can't be serialized to JSON: {error}");
+        return 0;
+      }
+    };
+    let data = detail.map(|detail| detail.to_string()).unwrap_or_default();
+
+    self.publish(Message::new(data).event(name))
+  }
+
+  /// Publishes the markup under the given event name, to be swapped by the
+  /// elements with a matching `sse-swap` attribute, refer to [sse_swap].
+  pub fn publish_markup(&self, event: impl Into<String>, markup: maud::Markup) -> usize {
+    self.publish(Message::new(markup.into_string()).event(event))
+  }
+
+  /// The number of subscribers currently connected
+  pub fn subscribers(&self) -> usize {
+    self.sender.receiver_count()
+  }
+}
+
+impl Default for Hub {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl actix_web::FromRequest for Hub {
+  type Error = actix_web::Error;
+
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    let hub = req.app_data::<Hub>().cloned().ok_or_else(|| {
+      actix_web::error::ErrorInternalServerError("the SSE hub is missing from the app data")
+    });
+
+    ready(hub)
+  }
+}
+
+/// A single Server-Sent Event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+  event: Option<String>,
+  id: Option<String>,
+  data: String
+}
+
+impl Message {
+  pub fn new(data: impl Into<String>) -> Self {
+    Self {
+      event: None,
+      id: None,
+      data: data.into()
+    }
+  }
+
+  /// The name of the event, messages without one are `message` events. Line
+  /// breaks are removed as they would end the field.
+  pub fn event(mut self, event: impl Into<String>) -> Self {
+    self.event = Some(single_line(event.into()));
+    self
+  }
+
+  /// The id of the message, line breaks are removed like in [Message::event]
+  pub fn id(mut self, id: impl Into<String>) -> Self {
+    self.id = Some(single_line(id.into()));
+    self
+  }
+
+  pub fn event_name(&self) -> Option<&str> {
+    self.event.as_deref()
+  }
+
+  pub fn data(&self) -> &str {
+    &self.data
+  }
+}
+
+/// The wire format of the message, every line of the data gets its own `data:`
+/// field whether it ends with `\n`, `\r\n` or `\r`.
+impl std::fmt::Display for Message {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(event) = &self.event {
+      writeln!(f, "event: {event}")?;
+    }
+
+    if let Some(id) = &self.id {
+      writeln!(f, "id: {id}")?;
+    }
+
+    for line in self
+      .data
+      .split("\r\n")
+      .flat_map(|line| line.split(['\r', '\n']))
+    {
+      writeln!(f, "data: {line}")?;
+    }
+
+    writeln!(f)
+  }
+}
+
+fn single_line(value: String) -> String {
+  match value.contains(['\r', '\n']) {
+    true => value.replace(['\r', '\n'], ""),
+    false => value
+  }
+}
+
+/// The receiving end of a [Hub::subscribe] call
+pub struct Subscription {
+  receiver: broadcast::Receiver<Message>
+}
+
+impl Subscription {
+  /// Waits for the next message, returns `None` once the hub is dropped
+  pub async fn recv(&mut self) -> Option<Message> {
+    loop {
+      match self.receiver.recv().await {
+        Ok(message) => return Some(message),
+        Err(RecvError::Lagged(skipped)) => lagged(skipped),
+        Err(RecvError::Closed) => return None
+      }
+    }
+  }
+
+  /// Returns the next message if one was already published
+  pub fn try_recv(&mut self) -> Option<Message> {
+    loop {
+      match self.receiver.try_recv() {
+        Ok(message) => return Some(message),
+        Err(TryRecvError::Lagged(skipped)) => lagged(skipped),
+        Err(_) => return None
+      }
+    }
+  }
+}
+
+fn lagged(skipped: u64) {
+  log::warn!("an SSE subscriber fell behind, {skipped} messages were skipped");
+}
+
+/// The body of the [Hub::response], streams the messages in their wire format
+struct EventStream {
+  next: Pin<Box<dyn Future<Output = (Option<Message>, Subscription)>>>
+}
+
+impl EventStream {
+  fn new(subscription: Subscription) -> Self {
+    Self {
+      next: Box::pin(Self::next(subscription))
+    }
+  }
+
+  /// The subscription is moved in and out of the future so it can be awaited
+  /// again once the message is out.
+  async fn next(mut subscription: Subscription) -> (Option<Message>, Subscription) {
+    (subscription.recv().await, subscription)
+  }
+}
+
+impl futures_core::Stream for EventStream {
+  type Item = Result<Bytes, Infallible>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let (message, subscription) = std::task::ready!(self.next.as_mut().poll(cx));
+    self.next = Box::pin(Self::next(subscription));
+
+    Poll::Ready(message.map(|message| Ok(Bytes::from(message.to_string()))))
+  }
+}
+
+/// The URL of a `SSE` endpoint, returned by the `sse_connect()` function the
+/// [endpoints!](crate::endpoints) macro generates for them.
+///
+/// It renders as the URL so it can be used as the value of the `sse-connect`
+/// attribute:
+/// ```rs
+/// div hx-ext="sse" sse-connect=(api::stream::sse_connect()) {}
+/// ```
+pub struct SseConnect {
+  url: String
+}
+
+impl SseConnect {
+  pub fn new(url: String) -> Self {
+    Self { url }
+  }
+
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  /// Renders the element that connects to the endpoint, along with the
+  /// `hx-ext="sse"` attribute that enables the extension.
+  pub fn element(&self, tag: &'static str, content: impl maud::Render) -> maud::Markup {
+    crate::htmx::element(
+      tag,
+      [
+        ("hx-ext", "sse".to_owned()),
+        ("sse-connect", self.url.clone())
+      ],
+      content
+    )
+  }
+}
+
+impl maud::Render for SseConnect {
+  fn render_to(&self, buffer: &mut String) {
+    self.url.render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn message_format() {
+    assert_eq!(Message::new("hello").to_string(), "data: hello\n\n");
+    assert_eq!(
+      Message::new("").event("reload").id("3").to_string(),
+      "event: reload\nid: 3\ndata: \n\n"
+    );
+  }
+
+  #[test]
+  fn swap_matches_the_published_name() {
+    let name = "todos\r\nlist";
+    let message = Message::new("").event(name);
+
+    assert_eq!(sse_swap(name), "todoslist");
+    assert_eq!(message.event_name(), Some(sse_swap(name).as_str()));
+    assert_eq!(
+      maud::html!(ul sse-swap=(sse_swap("a&b")) {}).into_string(),
+      r#"<ul sse-swap="a&amp;b"></ul>"#
+    );
+  }
+
+  #[test]
+  fn connect_element() {
+    let connect = SseConnect::new("/stream?a=1&b=2".to_owned());
+
+    assert_eq!(
+      connect.element("div", maud::html!(p {"x"})).into_string(),
+      r#"<div hx-ext="sse" sse-connect="/stream?a=1&amp;b=2"><p>x</p></div>"#
+    );
+  }
+
+  #[test]
+  fn every_line_break_splits_the_data() {
+    assert_eq!(
+      Message::new("a\nb\r\nc\rd").to_string(),
+      "data: a\ndata: b\ndata: c\ndata: d\n\n"
+    );
+  }
+
+  #[test]
+  fn line_breaks_are_removed_from_the_fields() {
+    let message = Message::new("x").event("reload\r\ndata: forged").id("1\n2");
+
+    assert_eq!(message.event_name(), Some("reloaddata: forged"));
+    assert_eq!(
+      message.to_string(),
+      "event: reloaddata: forged\nid: 12\ndata: x\n\n"
+    );
+  }
+
+  #[test]
+  fn messages_fan_out_to_every_subscriber() {
+    let hub = Hub::new();
+    let mut subscriptions = [hub.subscribe(), hub.subscribe(), hub.subscribe()];
+
+    assert_eq!(hub.publish(Message::new("a")), 3);
+    for subscription in &mut subscriptions {
+      assert_eq!(subscription.try_recv(), Some(Message::new("a")));
+      assert_eq!(subscription.try_recv(), None);
+    }
+  }
+
+  #[test]
+  fn disconnected_subscribers_are_pruned() {
+    let hub = Hub::new();
+    let mut kept = hub.subscribe();
+    let dropped = hub.subscribe();
+    assert_eq!(hub.subscribers(), 2);
+
+    drop(dropped);
+    assert_eq!(hub.subscribers(), 1);
+    assert_eq!(hub.publish(Message::new("a")), 1);
+    assert_eq!(kept.try_recv(), Some(Message::new("a")));
+  }
+
+  #[test]
+  fn lagging_subscribers_skip_the_oldest_messages() {
+    let hub = Hub::with_capacity(2);
+    let mut subscription = hub.subscribe();
+
+    for data in ["a", "b", "c"] {
+      hub.publish(Message::new(data));
+    }
+
+    assert_eq!(subscription.try_recv(), Some(Message::new("b")));
+    assert_eq!(subscription.try_recv(), Some(Message::new("c")));
+    assert_eq!(subscription.try_recv(), None);
+  }
+
+  #[tokio::test]
+  async fn recv_ends_once_the_hub_is_dropped() {
+    let hub = Hub::new();
+    let mut subscription = hub.subscribe();
+
+    hub.publish_event(crate::TriggerEvent::new("reload").field("id", &3).unwrap());
+    drop(hub);
+
+    assert_eq!(
+      subscription.recv().await,
+      Some(Message::new(r#"{"id":3}"#).event("reload"))
+    );
+    assert_eq!(subscription.recv().await, None);
+  }
+}
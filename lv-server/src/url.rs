@@ -0,0 +1,117 @@
+//! Helpers used by the `url()` functions generated by the [endpoints!](crate::endpoints)
+//! macro to safely insert values into the routes.
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+
+/// Every character except the unreserved ones from RFC 3986 is encoded, so a
+/// value can never escape the segment it's inserted into.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+  .remove(b'-')
+  .remove(b'.')
+  .remove(b'_')
+  .remove(b'~');
+
+/// Same as [SEGMENT] but the `/` are kept so the value can span multiple
+/// segments.
+const TAIL: &AsciiSet = &SEGMENT.remove(b'/');
+
+/// Percent-encodes the value so it can safely be used as a single segment of a
+/// path, `/`, `?`, `#` and spaces included:
+/// ```
+/// # use lv_server::url::encode_segment;
+/// assert_eq!(encode_segment("a/b c"), "a%2Fb%20c");
+/// ```
+pub fn encode_segment(value: impl std::fmt::Display) -> String {
+  percent_encoding::utf8_percent_encode(&value.to_string(), SEGMENT).to_string()
+}
+
+/// Percent-encodes the value while preserving its `/`, for the catch-all
+/// params of a route like `{tail:.*}` that are expected to span multiple
+/// segments:
+/// ```
+/// # use lv_server::url::encode_tail;
+/// assert_eq!(encode_tail("docs/a b"), "docs/a%20b");
+/// ```
+pub fn encode_tail(value: impl std::fmt::Display) -> String {
+  percent_encoding::utf8_percent_encode(&value.to_string(), TAIL).to_string()
+}
+
+/// The error returned when the query params can't be represented as a flat
+/// list of key/values, for example if one of them is a `Vec` or a nested
+/// struct.
+pub use serde_urlencoded::ser::Error as QueryError;
+
+/// Appends the query params to the url, encoded using `serde_urlencoded`.
+/// Params with a `None` value are skipped.
+pub fn with_query(url: String, query: &impl serde::Serialize) -> Result<String, QueryError> {
+  let query = serde_urlencoded::to_string(query)?;
+
+  Ok(match query.is_empty() {
+    true => url,
+    false => format!("{url}?{query}")
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn segment_encodes_reserved_characters() {
+    assert_eq!(encode_segment("a?b#c&d=e"), "a%3Fb%23c%26d%3De");
+    assert_eq!(encode_segment("100%"), "100%25");
+    assert_eq!(encode_segment("a+b c"), "a%2Bb%20c");
+    assert_eq!(encode_segment("-._~"), "-._~");
+  }
+
+  #[test]
+  fn slashes_in_segment_and_tail() {
+    assert_eq!(encode_segment("../admin"), "..%2Fadmin");
+    assert_eq!(encode_tail("docs/guide/intro"), "docs/guide/intro");
+    assert_eq!(encode_tail("docs/a?b"), "docs/a%3Fb");
+  }
+
+  #[test]
+  fn non_ascii_is_encoded_as_utf8() {
+    assert_eq!(encode_segment("café"), "caf%C3%A9");
+    assert_eq!(encode_tail("日本/語"), "%E6%97%A5%E6%9C%AC/%E8%AA%9E");
+  }
+
+  #[test]
+  fn query_skips_none() {
+    let query = [("page", Some("2")), ("filter", None)];
+
+    assert_eq!(
+      with_query("/todos".to_owned(), &query).unwrap(),
+      "/todos?page=2"
+    );
+    assert_eq!(
+      with_query("/todos".to_owned(), &[("a", None::<&str>)]).unwrap(),
+      "/todos"
+    );
+  }
+
+  #[test]
+  fn query_rejects_nested_values() {
+    #[derive(serde::Serialize)]
+    struct Tags {
+      tags: Vec<String>
+    }
+
+    #[derive(serde::Serialize)]
+    struct Nested {
+      page: Option<usize>,
+      filter: Tags
+    }
+
+    let tags = Tags {
+      tags: vec!["a".to_owned(), "b".to_owned()]
+    };
+    assert!(with_query("/todos".to_owned(), &tags).is_err());
+
+    let nested = Nested {
+      page: Some(2),
+      filter: Tags { tags: Vec::new() }
+    };
+    assert!(with_query("/todos".to_owned(), &nested).is_err());
+  }
+}
@@ -24,7 +24,7 @@ use crate::WithRouter;
 ///   fn render(data: ApiData) -> Markup {
 ///     html!(
 ///       .fdn.col.justify-center.items.center {
-///         (fragments::TodoList::render(&data.todos()))
+///         (fragments::TodoList::render(&data.todos(), 0))
 ///         (fragments::AddTodoForm::render())
 ///       }
 ///     )
@@ -1,18 +1,440 @@
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
 use actix_web::HttpResponse;
 
 pub trait WithTrigger {
-  fn into_trigger(self) -> &'static str;
+  /// The event as it's sent to the client, fails when the fields of the event
+  /// can't be serialized to JSON.
+  fn into_trigger(self) -> Result<TriggerEvent, serde_json::Error>;
 
+  /// Adds the event to the response, a response with a
+  /// 500 Internal Server Error status is sent instead when the event can't be
+  /// serialized.
   fn trigger(self, res: HttpResponse) -> HttpResponse
   where
     Self: Sized
   {
-    super::responses::trigger(res, self.into_trigger())
+    match self.into_trigger() {
+      Ok(event) => super::responses::trigger(res, event),
+      Err(error) => serialization_error(error)
+    }
+  }
+
+  /// Sends the event after the swapping step rather than as soon as the
+  /// response is received.
+  fn after_swap(self) -> Result<TriggerEvent, serde_json::Error>
+  where
+    Self: Sized
+  {
+    Ok(self.into_trigger()?.phase(TriggerPhase::AfterSwap))
+  }
+
+  /// Sends the event after the settling step rather than as soon as the
+  /// response is received.
+  fn after_settle(self) -> Result<TriggerEvent, serde_json::Error>
+  where
+    Self: Sized
+  {
+    Ok(self.into_trigger()?.phase(TriggerPhase::AfterSettle))
   }
 }
 
 impl WithTrigger for () {
-  fn into_trigger(self) -> &'static str {
-    ""
+  fn into_trigger(self) -> Result<TriggerEvent, serde_json::Error> {
+    Ok(TriggerEvent::new(""))
+  }
+}
+
+impl WithTrigger for TriggerEvent {
+  fn into_trigger(self) -> Result<TriggerEvent, serde_json::Error> {
+    Ok(self)
+  }
+}
+
+/// Lets the events built with [TriggerEvent::detail] or
+/// [TriggerEvent::field], or returned by [Fragment::emit](crate::Fragment::emit),
+/// be sent without unwrapping them first.
+impl WithTrigger for Result<TriggerEvent, serde_json::Error> {
+  fn into_trigger(self) -> Result<TriggerEvent, serde_json::Error> {
+    self
+  }
+}
+
+fn serialization_error(error: serde_json::Error) -> HttpResponse {
+  log::error!("the detail of an event can't be serialized to JSON: {error}");
+
+  actix_web::ResponseError::error_response(&error)
+}
+
+/// Implemented by the `events!` enums declared for a fragment with
+/// `events!(TodoListEvents for TodoList { .. })`, whose events are namespaced
+/// with the [Fragment::ID](crate::Fragment::ID) of the fragment. Only these
+/// enums can be sent with [Fragment::emit](crate::Fragment::emit):
+/// ```compile_fail
+/// use lv_server::Fragment;
+///
+/// pub struct TodoList;
+///
+/// impl lv_server::Fragment<TodoListEvents, ()> for TodoList {
+///   const ID: &'static str = "TodoList";
+/// }
+///
+/// // missing `for TodoList`, the events wouldn't be namespaced
+/// lv_server::events!(TodoListEvents {
+///   Reload "from:body"
+/// });
+///
+/// TodoList::emit(TodoListEvents::Reload);
+/// ```
+pub trait FragmentEvents: WithTrigger {
+  /// The fragment that namespaces the events
+  type Fragment;
+}
+
+/// A collection of events sent together in a single response, implemented
+/// for tuples of [WithTrigger] so events from different `events!` enums can
+/// be mixed, as well as for vectors and arrays of a single type of event.
+///
+/// ```rs
+/// markup.into_response_with_events((TodoListEvents::Reload, HeaderEvents::Refresh))
+/// ```
+pub trait WithTriggers {
+  fn into_triggers(self) -> Result<Vec<TriggerEvent>, serde_json::Error>;
+
+  /// Same as [WithTrigger::trigger], none of the events are sent if one of
+  /// them can't be serialized.
+  fn trigger(self, res: HttpResponse) -> HttpResponse
+  where
+    Self: Sized
+  {
+    match self.into_triggers() {
+      Ok(events) => super::responses::triggers(res, events),
+      Err(error) => serialization_error(error)
+    }
+  }
+}
+
+impl<T: WithTrigger> WithTriggers for Vec<T> {
+  fn into_triggers(self) -> Result<Vec<TriggerEvent>, serde_json::Error> {
+    self.into_iter().map(WithTrigger::into_trigger).collect()
+  }
+}
+
+impl<T: WithTrigger, const N: usize> WithTriggers for [T; N] {
+  fn into_triggers(self) -> Result<Vec<TriggerEvent>, serde_json::Error> {
+    self.into_iter().map(WithTrigger::into_trigger).collect()
+  }
+}
+
+macro_rules! impl_with_triggers_for_tuple {
+  ($($event:ident),+) => {
+    impl<$($event: WithTrigger),+> WithTriggers for ($($event,)+) {
+      #[allow(non_snake_case)]
+      fn into_triggers(self) -> Result<Vec<TriggerEvent>, serde_json::Error> {
+        let ($($event,)+) = self;
+
+        Ok(vec![$($event.into_trigger()?),+])
+      }
+    }
+  };
+}
+
+impl_with_triggers_for_tuple!(A);
+impl_with_triggers_for_tuple!(A, B);
+impl_with_triggers_for_tuple!(A, B, C);
+impl_with_triggers_for_tuple!(A, B, C, D);
+impl_with_triggers_for_tuple!(A, B, C, D, E);
+impl_with_triggers_for_tuple!(A, B, C, D, E, F);
+impl_with_triggers_for_tuple!(A, B, C, D, E, F, G);
+impl_with_triggers_for_tuple!(A, B, C, D, E, F, G, H);
+
+/// An event as it's sent to the client in the HX-Trigger headers, with an
+/// optional detail that HTMX exposes as `event.detail` to the listeners.
+///
+/// Events without detail are sent as a comma-separated list of names, as soon
+/// as one of them has a detail the JSON form of the header is used instead:
+/// ```json
+/// {"TodoListEvents_Updated": {"id": 3}}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerEvent {
+  name: Cow<'static, str>,
+  detail: Option<serde_json::Value>,
+  phase: TriggerPhase
+}
+
+impl TriggerEvent {
+  pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+    Self {
+      name: name.into(),
+      detail: None,
+      phase: TriggerPhase::default()
+    }
+  }
+
+  /// Sets when the event is triggered on the client
+  pub fn phase(mut self, phase: TriggerPhase) -> Self {
+    self.phase = phase;
+    self
+  }
+
+  /// Sets the whole detail of the event, fails if it can't be serialized to
+  /// JSON.
+  pub fn detail(mut self, detail: &impl serde::Serialize) -> Result<Self, serde_json::Error> {
+    self.detail = Some(serde_json::to_value(detail)?);
+    Ok(self)
+  }
+
+  /// Adds a field to the detail of the event, turning it into an object if
+  /// it wasn't one already. Fails if the value can't be serialized to JSON.
+  pub fn field(
+    mut self, name: &str, value: &impl serde::Serialize
+  ) -> Result<Self, serde_json::Error> {
+    let value = serde_json::to_value(value)?;
+
+    match &mut self.detail {
+      Some(serde_json::Value::Object(fields)) => {
+        fields.insert(name.to_owned(), value);
+      }
+      detail => {
+        *detail = Some(serde_json::Value::Object(serde_json::Map::from_iter([(
+          name.to_owned(),
+          value
+        )])));
+      }
+    };
+
+    Ok(self)
+  }
+
+  /// Prefixes the name of the event with the given namespace, `TodoList` turns
+  /// `TodoListEvents_Reload` into `TodoList:TodoListEvents_Reload`.
+  pub fn namespace(mut self, namespace: &str) -> Self {
+    self.name = Cow::Owned(format!("{namespace}:{}", self.name));
+    self
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn get_phase(&self) -> TriggerPhase {
+    self.phase
+  }
+
+  pub fn into_parts(self) -> (Cow<'static, str>, Option<serde_json::Value>) {
+    (self.name, self.detail)
+  }
+}
+
+impl From<&'static str> for TriggerEvent {
+  fn from(name: &'static str) -> Self {
+    Self::new(name)
+  }
+}
+
+impl From<String> for TriggerEvent {
+  fn from(name: String) -> Self {
+    Self::new(name)
+  }
+}
+
+/// When an event is triggered on the client, each phase is sent through its own
+/// header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPhase {
+  /// As soon as the response is received, using the HX-Trigger header
+  #[default]
+  Receive,
+
+  /// Once the new content has been swapped in, using the HX-Trigger-After-Swap
+  /// header
+  AfterSwap,
+
+  /// Once the new content has been swapped in and settled, using the
+  /// HX-Trigger-After-Settle header
+  AfterSettle
+}
+
+impl TriggerPhase {
+  pub fn header(&self) -> &'static str {
+    match self {
+      TriggerPhase::Receive => "HX-Trigger",
+      TriggerPhase::AfterSwap => "HX-Trigger-After-Swap",
+      TriggerPhase::AfterSettle => "HX-Trigger-After-Settle"
+    }
+  }
+}
+
+/// The listening side of an event, generated by the `events!` macro for each
+/// event as a constant:
+/// ```rs
+/// div
+///   hx-trigger=(TodoListEvents::UPDATED)
+///   hx-vals=(TodoListEvents::UPDATED.vals())
+///   hx-get=(api::get_todo_list::hx_get())
+///   {}
+/// ```
+///
+/// It renders to the `hx-trigger` value of the event, while [EventListener::vals]
+/// forwards the fields of the event's detail to the follow-up request. The
+/// listeners of the events owned by a fragment are namespaced like the events
+/// themselves, refer to [Fragment::emit](crate::Fragment::emit).
+pub struct EventListener<Events> {
+  name: &'static str,
+  namespace: Option<&'static str>,
+  filter: Option<&'static str>,
+  modifier: &'static str,
+  fields: &'static [&'static str],
+  events: PhantomData<fn() -> Events>
+}
+
+impl<Events> EventListener<Events> {
+  pub const fn new(
+    name: &'static str, modifier: &'static str, fields: &'static [&'static str]
+  ) -> Self {
+    Self {
+      name,
+      namespace: None,
+      filter: None,
+      modifier,
+      fields,
+      events: PhantomData
+    }
+  }
+
+  /// Prefixes the name of the event with the namespace, like
+  /// [TriggerEvent::namespace] does.
+  pub const fn namespaced(mut self, namespace: &'static str) -> Self {
+    self.namespace = Some(namespace);
+    self
+  }
+
+  /// Only listens to the events for which the filter is true, like
+  /// [Trigger::filter](crate::htmx::Trigger::filter)
+  pub const fn filtered(mut self, filter: &'static str) -> Self {
+    self.filter = Some(filter);
+    self
+  }
+
+  pub const fn filter(&self) -> Option<&'static str> {
+    self.filter
+  }
+
+  /// The name of the event, without its namespace
+  pub const fn name(&self) -> &'static str {
+    self.name
+  }
+
+  pub const fn namespace(&self) -> Option<&'static str> {
+    self.namespace
+  }
+
+  /// The name of the event as it's sent, along with its namespace
+  pub fn event_name(&self) -> String {
+    match self.namespace {
+      Some(namespace) => format!("{namespace}:{}", self.name),
+      None => self.name.to_owned()
+    }
+  }
+
+  pub const fn modifier(&self) -> &'static str {
+    self.modifier
+  }
+
+  pub const fn fields(&self) -> &'static [&'static str] {
+    self.fields
+  }
+
+  /// A [Trigger](crate::htmx::Trigger) for the event without the filter and
+  /// the modifiers of its declaration, to listen to it with different modifiers:
+  /// ```rs
+  /// li hx-trigger=(TodoListEvents::UPDATED.listen().from_closest("ul").delay(Duration::from_millis(500))) {}
+  /// ```
+  pub fn listen(&self) -> crate::htmx::Trigger {
+    crate::htmx::Trigger::event(self.event_name())
+  }
+
+  /// A `hx-vals` value that includes the fields of the event's detail in the
+  /// request sent by the listener, `js:{id: event.detail.id}` for example.
+  pub fn vals(&self) -> String {
+    let fields: Vec<String> = self
+      .fields
+      .iter()
+      .map(|field| format!("{field}: event.detail.{field}"))
+      .collect();
+
+    format!("js:{{{}}}", fields.join(", "))
+  }
+}
+
+impl<Events> Clone for EventListener<Events> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<Events> Copy for EventListener<Events> {}
+
+impl<Events> std::fmt::Debug for EventListener<Events> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("EventListener")
+      .field("name", &self.name)
+      .field("namespace", &self.namespace)
+      .field("filter", &self.filter)
+      .field("modifier", &self.modifier)
+      .field("fields", &self.fields)
+      .finish()
+  }
+}
+
+impl<Events> maud::Render for EventListener<Events> {
+  fn render_to(&self, buffer: &mut String) {
+    buffer.push_str(&self.event_name());
+
+    if let Some(filter) = self.filter {
+      buffer.push('[');
+      buffer.push_str(filter);
+      buffer.push(']');
+    }
+
+    if !self.modifier.is_empty() {
+      buffer.push(' ');
+      buffer.push_str(self.modifier);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use actix_web::http::StatusCode;
+
+  use super::*;
+
+  /// JSON objects only have string keys
+  fn unserializable() -> Result<TriggerEvent, serde_json::Error> {
+    TriggerEvent::new("saved").field("cells", &BTreeMap::from([((0, 0), 1)]))
+  }
+
+  #[test]
+  fn unserializable_details_are_errors() {
+    assert!(unserializable().is_err());
+    assert!(TriggerEvent::new("saved")
+      .detail(&BTreeMap::from([((0, 0), 1)]))
+      .is_err());
+  }
+
+  #[test]
+  fn unserializable_events_are_server_errors() {
+    let res = unserializable().trigger(HttpResponse::Ok().finish());
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(res.headers().get("HX-Trigger").is_none());
+
+    let res = (TriggerEvent::new("reload"), unserializable()).trigger(HttpResponse::Ok().finish());
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(res.headers().get("HX-Trigger").is_none());
   }
 }
@@ -0,0 +1,385 @@
+//! WebSocket support, for the screens that send messages to the server without
+//! a request per message and receive markup from it.
+//!
+//! An endpoint is declared with the `WS` verb and hands its connections to the
+//! [Hub] along with the function that handles the incoming messages:
+//! ```rs
+//! lv_server::endpoints!(Chat {
+//!   socket => WS "/socket"
+//! });
+//!
+//! #[derive(Deserialize)]
+//! #[serde(tag = "action")]
+//! enum ChatMessage {
+//!   Send { text: String }
+//! }
+//!
+//! impl api::socket::Router {
+//!   pub async fn endpoint(
+//!     req: HttpRequest, body: web::Payload, hub: ws::Hub
+//!   ) -> actix_web::Result<HttpResponse> {
+//!     hub.connect(&req, body, |session: ws::Session, message: ChatMessage| async move {
+//!       match message {
+//!         ChatMessage::Send { text } => session.broadcast(Chat::render_message(&text))
+//!       };
+//!     })
+//!   }
+//! }
+//! ```
+//!
+//! The client side relies on the [HTMX WebSocket extension](https://htmx.org/extensions/ws/),
+//! which sends the values of the forms as JSON and swaps the markup it receives
+//! into the elements with the same ids:
+//! ```rs
+//! div hx-ext="ws" ws-connect=(api::socket::ws_connect()) {
+//!   div id="messages" {}
+//!   form ws-send { input name="text"; input type="hidden" name="action" value="Send"; }
+//! }
+//! ```
+//!
+//! # CSRF
+//! Browsers can't add custom headers to the handshake, the connection is
+//! therefore only accepted for same-site requests while the CSRF header is
+//! expected in the `HEADERS` HTMX adds to each message. Refer to
+//! [csrf::is_ws_handshake_safe](crate::csrf::is_ws_handshake_safe) and
+//! [csrf::is_ws_message_safe](crate::csrf::is_ws_message_safe).
+use std::collections::HashMap;
+use std::future::{ready, Future, Ready};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use actix_web::{HttpRequest, HttpResponse};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// Keeps track of the connected sessions to send them markup. Cloning a hub is
+/// cheap and all the clones share the same sessions.
+///
+/// The hub is expected to be added to the app data of the Actix app, it can
+/// then be extracted in the handlers:
+/// ```rs
+/// App::new().app_data(ws_hub.clone())
+/// ```
+///
+/// The markup is buffered for each session up to the capacity of the hub, a
+/// session that falls further behind is disconnected so the extension
+/// reconnects it to a fresh page rather than letting the buffer grow.
+#[derive(Clone)]
+pub struct Hub {
+  sessions: Arc<Mutex<HashMap<usize, Sender<String>>>>,
+  next_id: Arc<AtomicUsize>,
+  capacity: usize
+}
+
+impl Hub {
+  /// The number of messages buffered for each session by default
+  pub const CAPACITY: usize = 64;
+
+  pub fn new() -> Self {
+    Self::with_capacity(Self::CAPACITY)
+  }
+
+  /// A hub that buffers up to `capacity` messages for each session
+  ///
+  /// # Panics
+  /// If the capacity is 0.
+  pub fn with_capacity(capacity: usize) -> Self {
+    assert!(
+      capacity > 0,
+      "the capacity of the WebSocket hub must be positive"
+    );
+
+    Self {
+      sessions: Arc::default(),
+      next_id: Arc::default(),
+      capacity
+    }
+  }
+
+  /// Upgrades the request to a WebSocket connection, every text message the
+  /// client sends is deserialized into `M` before being given to `on_message`
+  /// along with the [Session] it comes from.
+  ///
+  /// Messages without the CSRF header or that can't be deserialized are
+  /// ignored.
+  pub fn connect<M, F, Fut>(
+    &self, req: &HttpRequest, body: actix_web::web::Payload, on_message: F
+  ) -> actix_web::Result<HttpResponse>
+  where
+    M: serde::de::DeserializeOwned + 'static,
+    F: Fn(Session, M) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static
+  {
+    if !crate::csrf::is_ws_handshake_safe(req) {
+      return Err(actix_web::error::ErrorForbidden(
+        "cross-site WebSocket connections are not allowed"
+      ));
+    }
+
+    let (response, mut socket, mut messages) = actix_ws::handle(req, body)?;
+    let (session, mut receiver) = self.register();
+
+    // forwards the markup sent to the session, until the session is
+    // disconnected by the hub
+    let mut outgoing = socket.clone();
+    actix_web::rt::spawn(async move {
+      while let Some(markup) = receiver.recv().await {
+        if outgoing.text(markup).await.is_err() {
+          return;
+        }
+      }
+
+      let _ = outgoing.close(None).await;
+    });
+
+    let req = req.clone();
+    actix_web::rt::spawn(async move {
+      while let Some(Ok(message)) = messages.recv().await {
+        match message {
+          actix_ws::Message::Text(text) => {
+            if let Some(message) = parse_message::<M>(&req, &text) {
+              on_message(session.clone(), message).await;
+            }
+          }
+          actix_ws::Message::Ping(bytes) => {
+            let Ok(()) = socket.pong(&bytes).await else {
+              break;
+            };
+          }
+          actix_ws::Message::Close(_) => break,
+          _ => {}
+        }
+      }
+
+      session.hub.disconnect(session.id);
+      let _ = socket.close(None).await;
+    });
+
+    Ok(response)
+  }
+
+  /// Sends the markup to a single session, returns whether the session is
+  /// still connected.
+  pub fn send(&self, session: usize, markup: maud::Markup) -> bool {
+    let mut sessions = self.sessions.lock().unwrap();
+
+    let Some(sender) = sessions.get(&session) else {
+      return false;
+    };
+
+    let sent = queue(session, sender, markup.into_string());
+    if !sent {
+      sessions.remove(&session);
+    }
+
+    sent
+  }
+
+  /// Sends the markup to every connected session, returns the number of
+  /// sessions that received it.
+  pub fn broadcast(&self, markup: maud::Markup) -> usize {
+    let markup = markup.into_string();
+    let mut sessions = self.sessions.lock().unwrap();
+    sessions.retain(|session, sender| queue(*session, sender, markup.clone()));
+
+    sessions.len()
+  }
+
+  /// The number of sessions currently connected
+  pub fn sessions(&self) -> usize {
+    let mut sessions = self.sessions.lock().unwrap();
+    sessions.retain(|_, sender| !sender.is_closed());
+
+    sessions.len()
+  }
+
+  /// Registers a new session, along with the receiving end of the markup
+  /// sent to it.
+  fn register(&self) -> (Session, Receiver<String>) {
+    let (sender, receiver) = channel(self.capacity);
+    let session = Session {
+      id: self.next_id.fetch_add(1, Ordering::Relaxed),
+      hub: self.clone()
+    };
+    self.sessions.lock().unwrap().insert(session.id, sender);
+
+    (session, receiver)
+  }
+
+  fn disconnect(&self, session: usize) {
+    self.sessions.lock().unwrap().remove(&session);
+  }
+}
+
+impl Default for Hub {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Queues the markup for the session, returns false if the session is gone or
+/// fell too far behind, in which case it should be disconnected.
+fn queue(session: usize, sender: &Sender<String>, markup: String) -> bool {
+  match sender.try_send(markup) {
+    Ok(()) => true,
+    Err(TrySendError::Full(_)) => {
+      log::warn!("the WebSocket session {session} fell behind and was disconnected");
+      false
+    }
+    Err(TrySendError::Closed(_)) => false
+  }
+}
+
+impl actix_web::FromRequest for Hub {
+  type Error = actix_web::Error;
+
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+    let hub = req.app_data::<Hub>().cloned().ok_or_else(|| {
+      actix_web::error::ErrorInternalServerError("the WebSocket hub is missing from the app data")
+    });
+
+    ready(hub)
+  }
+}
+
+/// The connection a message comes from, given to the function handling the
+/// messages of a [Hub::connect] call.
+#[derive(Clone)]
+pub struct Session {
+  id: usize,
+  hub: Hub
+}
+
+impl Session {
+  pub fn id(&self) -> usize {
+    self.id
+  }
+
+  /// Sends the markup to this session only
+  pub fn send(&self, markup: maud::Markup) -> bool {
+    self.hub.send(self.id, markup)
+  }
+
+  /// Sends the markup to every connected session, including this one
+  pub fn broadcast(&self, markup: maud::Markup) -> usize {
+    self.hub.broadcast(markup)
+  }
+
+  pub fn hub(&self) -> &Hub {
+    &self.hub
+  }
+}
+
+/// Deserializes a message sent by the HTMX extension, once its CSRF header
+/// was verified.
+fn parse_message<M: serde::de::DeserializeOwned>(req: &HttpRequest, text: &str) -> Option<M> {
+  let mut message: serde_json::Value = serde_json::from_str(text).ok()?;
+  let headers = message.as_object_mut()?.remove("HEADERS")?;
+
+  if !crate::csrf::is_ws_message_safe(req, &headers) {
+    return None;
+  }
+
+  serde_json::from_value(message).ok()
+}
+
+/// The URL of a `WS` endpoint, returned by the `ws_connect()` function the
+/// [endpoints!](crate::endpoints) macro generates for them.
+///
+/// It renders as the URL so it can be used as the value of the `ws-connect`
+/// attribute:
+/// ```rs
+/// div hx-ext="ws" ws-connect=(api::socket::ws_connect()) {}
+/// ```
+pub struct WsConnect {
+  url: String
+}
+
+impl WsConnect {
+  pub fn new(url: String) -> Self {
+    Self { url }
+  }
+
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  /// Renders the element that connects to the endpoint, along with the
+  /// `hx-ext="ws"` attribute that enables the extension.
+  pub fn element(&self, tag: &'static str, content: impl maud::Render) -> maud::Markup {
+    crate::htmx::element(
+      tag,
+      [
+        ("hx-ext", "ws".to_owned()),
+        ("ws-connect", self.url.clone())
+      ],
+      content
+    )
+  }
+}
+
+impl maud::Render for WsConnect {
+  fn render_to(&self, buffer: &mut String) {
+    self.url.render_to(buffer);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn connect_element() {
+    let connect = WsConnect::new("/chat?room=a&b".to_owned());
+
+    assert_eq!(
+      connect
+        .element("div", maud::html!(form ws-send {}))
+        .into_string(),
+      r#"<div hx-ext="ws" ws-connect="/chat?room=a&amp;b"><form ws-send></form></div>"#
+    );
+  }
+
+  #[test]
+  fn broadcast_reaches_every_session() {
+    let hub = Hub::new();
+    let (first, mut first_receiver) = hub.register();
+    let (_, mut second_receiver) = hub.register();
+
+    assert_eq!(hub.broadcast(maud::html!("a")), 2);
+    assert!(first.send(maud::html!("b")));
+
+    assert_eq!(first_receiver.try_recv().unwrap(), "a");
+    assert_eq!(first_receiver.try_recv().unwrap(), "b");
+    assert_eq!(second_receiver.try_recv().unwrap(), "a");
+    assert!(second_receiver.try_recv().is_err());
+  }
+
+  #[test]
+  fn closed_sessions_are_removed() {
+    let hub = Hub::new();
+    let (session, receiver) = hub.register();
+    drop(receiver);
+
+    assert!(!session.send(maud::html!("a")));
+    assert_eq!(hub.sessions(), 0);
+  }
+
+  #[test]
+  fn sessions_that_fall_behind_are_disconnected() {
+    let hub = Hub::with_capacity(2);
+    let (session, mut receiver) = hub.register();
+
+    assert_eq!(hub.broadcast(maud::html!("a")), 1);
+    assert_eq!(hub.broadcast(maud::html!("b")), 1);
+    assert_eq!(hub.broadcast(maud::html!("c")), 0);
+    assert!(!session.send(maud::html!("d")));
+
+    // the queued markup is still delivered before the channel closes
+    assert_eq!(receiver.try_recv().unwrap(), "a");
+    assert_eq!(receiver.try_recv().unwrap(), "b");
+    assert!(receiver.try_recv().is_err());
+  }
+}
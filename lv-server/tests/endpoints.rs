@@ -0,0 +1,127 @@
+//! The routes generated by the `endpoints!` macro, served by a real app
+use actix_web::http::{Method, StatusCode};
+use actix_web::test::{self, TestRequest};
+use actix_web::App;
+use lv_server::WithRouter;
+
+mod cache {
+  use actix_web::{HttpRequest, HttpResponse};
+
+  pub struct Cache;
+
+  impl lv_server::Fragment<(), api::Router> for Cache {
+    const ID: &'static str = "Cache";
+  }
+
+  lv_server::endpoints!(Cache {
+    any_index => ANY "/"
+    purge => METHOD("PURGE") "/{key}"
+  });
+
+  impl api::any_index::Router {
+    pub async fn endpoint(req: HttpRequest) -> HttpResponse {
+      HttpResponse::Ok().body(req.method().to_string())
+    }
+  }
+
+  impl api::purge::Router {
+    pub async fn endpoint(path: api::purge::Path) -> HttpResponse {
+      HttpResponse::Ok().body(format!("purged {}", path.key))
+    }
+  }
+}
+
+mod todos {
+  use actix_web::web::Form;
+  use actix_web::HttpResponse;
+
+  pub struct Todos;
+
+  impl lv_server::Fragment<(), api::Router> for Todos {
+    const ID: &'static str = "Todos";
+  }
+
+  lv_server::endpoints!(Todos {
+    edit_todo => GET | POST "/{index: usize}"
+  });
+
+  #[derive(serde::Deserialize)]
+  pub struct EditTodoForm {
+    text: String
+  }
+
+  impl api::edit_todo::Router {
+    pub async fn get(path: api::edit_todo::Path) -> HttpResponse {
+      HttpResponse::Ok().body(format!("edit {}", path.index))
+    }
+
+    pub async fn post(path: api::edit_todo::Path, Form(form): Form<EditTodoForm>) -> HttpResponse {
+      HttpResponse::Ok().body(format!("update {} to {}", path.index, form.text))
+    }
+  }
+}
+
+/// A request that passes the default CSRF checks
+fn request(method: Method, uri: &str) -> TestRequest {
+  TestRequest::default()
+    .method(method)
+    .uri(uri)
+    .insert_header(("X-LVSERVER-REQ", "1"))
+    .insert_header(("Sec-Fetch-Site", "same-origin"))
+}
+
+#[actix_web::test]
+async fn any_accepts_every_method() {
+  let app = test::init_service(App::new().configure(cache::Cache::router)).await;
+
+  for method in [
+    Method::GET,
+    Method::POST,
+    Method::DELETE,
+    Method::from_bytes(b"PURGE").unwrap()
+  ] {
+    let req = request(method.clone(), &cache::api::any_index::url());
+    let body = test::call_and_read_body(&app, req.to_request()).await;
+
+    assert_eq!(body, method.as_str());
+  }
+}
+
+#[actix_web::test]
+async fn custom_methods() {
+  let app = test::init_service(App::new().configure(cache::Cache::router)).await;
+  let url = cache::api::purge::url("a b");
+
+  let req = request(Method::from_bytes(b"PURGE").unwrap(), &url);
+  let body = test::call_and_read_body(&app, req.to_request()).await;
+  assert_eq!(body, "purged a b");
+
+  // the custom methods aren't safe, they go through the CSRF checks
+  let req = TestRequest::default()
+    .method(Method::from_bytes(b"PURGE").unwrap())
+    .uri(&url);
+  let res = test::call_service(&app, req.to_request()).await;
+  assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+  let req = request(Method::GET, &url);
+  let res = test::call_service(&app, req.to_request()).await;
+  assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn multiple_verbs_share_the_route() {
+  let app = test::init_service(App::new().configure(todos::Todos::router)).await;
+  let url = todos::api::edit_todo::url(3);
+
+  let req = request(Method::GET, &url);
+  let body = test::call_and_read_body(&app, req.to_request()).await;
+  assert_eq!(body, "edit 3");
+
+  let req = request(Method::POST, &url).set_form([("text", "milk")]);
+  let body = test::call_and_read_body(&app, req.to_request()).await;
+  assert_eq!(body, "update 3 to milk");
+
+  let req = request(Method::PUT, &url);
+  let res = test::call_service(&app, req.to_request()).await;
+  assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,71 @@
+//! The events generated by the `events!` macro, as they are sent and rendered
+use maud::Render;
+
+lv_server::events!(FormEvents {
+  Reset ""
+  Submit "[ctrlKey] from:body"
+  Saved { id: u64 } "from:closest form.a delay:1s"
+});
+
+#[test]
+fn events_render_with_their_modifiers() {
+  assert_eq!(FormEvents::Reset.render().into_string(), "FormEvents_Reset");
+  assert_eq!(
+    FormEvents::Submit.render().into_string(),
+    "FormEvents_Submit[ctrlKey] from:body"
+  );
+  assert_eq!(
+    FormEvents::Saved { id: 3 }.render().into_string(),
+    "FormEvents_Saved from:closest form.a delay:1s"
+  );
+}
+
+#[test]
+fn listeners_render_like_the_events() {
+  for (listener, event) in [
+    (FormEvents::RESET, FormEvents::Reset),
+    (FormEvents::SUBMIT, FormEvents::Submit),
+    (FormEvents::SAVED, FormEvents::Saved { id: 3 })
+  ] {
+    assert_eq!(
+      listener.render().into_string(),
+      event.render().into_string()
+    );
+  }
+
+  assert_eq!(FormEvents::SUBMIT.filter(), Some("ctrlKey"));
+  assert_eq!(FormEvents::SUBMIT.listen().to_string(), "FormEvents_Submit");
+}
+
+pub struct TodoList;
+
+impl lv_server::Fragment<TodoListEvents, ()> for TodoList {
+  const ID: &'static str = "TodoList";
+}
+
+lv_server::events!(TodoListEvents for TodoList {
+  Reload "from:body"
+  Updated { index: usize } ""
+});
+
+#[test]
+fn fragment_events_are_namespaced() {
+  use lv_server::Fragment;
+
+  let event = TodoList::emit(TodoListEvents::Reload).unwrap();
+  assert_eq!(event.name(), "TodoList:TodoListEvents_Reload");
+
+  let event = TodoList::emit(TodoListEvents::Updated { index: 3 }).unwrap();
+  let (name, detail) = event.into_parts();
+  assert_eq!(name, "TodoList:TodoListEvents_Updated");
+  assert_eq!(detail, Some(serde_json::json!({ "index": 3 })));
+
+  assert_eq!(
+    TodoList::listen(TodoListEvents::RELOAD).to_string(),
+    "TodoList:TodoListEvents_Reload from:body"
+  );
+  assert_eq!(
+    TodoListEvents::Reload.render().into_string(),
+    "TodoList:TodoListEvents_Reload from:body"
+  );
+}